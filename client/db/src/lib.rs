@@ -16,12 +16,16 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod parity_db_adapter;
+mod upgrade;
 mod utils;
 
+pub use upgrade::CURRENT_VERSION;
+
 pub use sp_database::Database;
 
-use std::{sync::Arc, path::{Path, PathBuf}, marker::PhantomData};
-use sp_core::H256;
+use std::{sync::Arc, path::{Path, PathBuf}, marker::PhantomData, collections::{BTreeMap, BTreeSet}};
+use sp_core::{H160, H256};
 use sp_runtime::traits::Block as BlockT;
 use parking_lot::Mutex;
 use codec::{Encode, Decode};
@@ -46,6 +50,13 @@ pub enum DatabaseSettingsSrc {
 		/// Cache size in MiB.
 		cache_size: usize,
 	},
+	/// Load a ParityDb database from a given path.
+	ParityDb {
+		/// Path to the database.
+		path: PathBuf,
+	},
+	/// Keep the database in memory, it is lost when the backend is dropped.
+	InMemory,
 }
 
 impl DatabaseSettingsSrc {
@@ -53,31 +64,41 @@ impl DatabaseSettingsSrc {
 	pub fn path(&self) -> Option<&Path> {
 		match self {
 			DatabaseSettingsSrc::RocksDb { path, .. } => Some(path.as_path()),
+			DatabaseSettingsSrc::ParityDb { path } => Some(path.as_path()),
+			DatabaseSettingsSrc::InMemory => None,
 		}
 	}
 }
 
 pub(crate) mod columns {
-	pub const NUM_COLUMNS: u32 = 4;
+	pub const NUM_COLUMNS: u32 = 6;
 
 	pub const META: u32 = 0;
 	pub const BLOCK_MAPPING: u32 = 1;
 	pub const TRANSACTION_MAPPING: u32 = 2;
 	pub const SYNCED_MAPPING: u32 = 3;
+	pub const TRACE_CACHE: u32 = 4;
+	pub const LOG_INDEX: u32 = 5;
 }
 
 pub(crate) mod static_keys {
 	pub const CURRENT_SYNCING_TIPS: &[u8] = b"CURRENT_SYNCING_TIPS";
+	pub const TRACE_CACHE_BLOCKS: &[u8] = b"TRACE_CACHE_BLOCKS";
+	pub const DATABASE_VERSION: &[u8] = b"DATABASE_VERSION";
 }
 
 pub struct Backend<Block: BlockT> {
 	meta: Arc<MetaDb<Block>>,
 	mapping: Arc<MappingDb<Block>>,
+	traces: Arc<TraceDb<Block>>,
+	logs: Arc<LogDb<Block>>,
 }
 
 impl<Block: BlockT> Backend<Block> {
+	/// Open the database, migrating it to the current version. Databases of a future version are
+	/// refused.
 	pub fn new(config: &DatabaseSettings) -> Result<Self, String> {
-		let db = utils::open_database(config)?;
+		let db = utils::open_database::<Block>(config)?;
 
 		Ok(Self {
 			mapping: Arc::new(MappingDb {
@@ -89,6 +110,16 @@ impl<Block: BlockT> Backend<Block> {
 				db: db.clone(),
 				_marker: PhantomData,
 			}),
+			traces: Arc::new(TraceDb {
+				db: db.clone(),
+				write_lock: Arc::new(Mutex::new(())),
+				_marker: PhantomData,
+			}),
+			logs: Arc::new(LogDb {
+				db: db.clone(),
+				write_lock: Arc::new(Mutex::new(())),
+				_marker: PhantomData,
+			}),
 		})
 	}
 
@@ -99,6 +130,14 @@ impl<Block: BlockT> Backend<Block> {
 	pub fn meta(&self) -> &Arc<MetaDb<Block>> {
 		&self.meta
 	}
+
+	pub fn traces(&self) -> &Arc<TraceDb<Block>> {
+		&self.traces
+	}
+
+	pub fn logs(&self) -> &Arc<LogDb<Block>> {
+		&self.logs
+	}
 }
 
 pub struct MetaDb<Block: BlockT> {
@@ -212,8 +251,11 @@ impl<Block: BlockT> MappingDb<Block> {
 			&commitment.block_hash.encode()
 		);
 
+		// A transaction is kept with every block it was included in, so that it can be resolved on
+		// whichever fork turns out canonical. Syncing a block again replaces its own entry.
 		for (i, ethereum_transaction_hash) in commitment.ethereum_transaction_hashes.into_iter().enumerate() {
 			let mut metadata = self.transaction_metadata(&ethereum_transaction_hash)?;
+			metadata.retain(|meta| meta.block_hash != commitment.block_hash);
 			metadata.push(TransactionMetadata::<Block> {
 				block_hash: commitment.block_hash,
 				ethereum_block_hash: commitment.ethereum_block_hash,
@@ -237,3 +279,627 @@ impl<Block: BlockT> MappingDb<Block> {
 		Ok(())
 	}
 }
+
+/// Maximum number of blocks whose traces are kept in the trace cache. Writing the traces of
+/// another block evicts the oldest entry.
+pub const TRACE_CACHE_MAX_BLOCKS: usize = 1024;
+
+/// Cache of the execution traces of a block, filled on demand by the trace RPC.
+/// Entries are keyed by substrate block hash, so each fork has its own. At most
+/// `TRACE_CACHE_MAX_BLOCKS` blocks are cached.
+pub struct TraceDb<Block: BlockT> {
+	db: Arc<dyn Database<DbHash>>,
+	write_lock: Arc<Mutex<()>>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> TraceDb<Block> {
+	pub fn block_traces<T: Decode>(
+		&self,
+		block_hash: &Block::Hash,
+	) -> Result<Option<T>, String> {
+		match self.db.get(crate::columns::TRACE_CACHE, &block_hash.encode()) {
+			Some(raw) => Ok(Some(T::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?)),
+			None => Ok(None),
+		}
+	}
+
+	/// Cached blocks, oldest first.
+	pub fn cached_blocks(&self) -> Result<Vec<Block::Hash>, String> {
+		match self.db.get(crate::columns::META, crate::static_keys::TRACE_CACHE_BLOCKS) {
+			Some(raw) => Ok(Vec::<Block::Hash>::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?),
+			None => Ok(Vec::new()),
+		}
+	}
+
+	pub fn write_block_traces<T: Encode>(
+		&self,
+		block_hash: &Block::Hash,
+		traces: &T,
+	) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		let mut blocks = self.cached_blocks()?;
+		blocks.retain(|hash| hash != block_hash);
+		blocks.push(*block_hash);
+
+		let mut transaction = sp_database::Transaction::new();
+
+		let evicted = blocks.len().saturating_sub(TRACE_CACHE_MAX_BLOCKS);
+		for hash in blocks.drain(..evicted) {
+			transaction.remove(crate::columns::TRACE_CACHE, &hash.encode());
+		}
+
+		transaction.set(
+			crate::columns::TRACE_CACHE,
+			&block_hash.encode(),
+			&traces.encode(),
+		);
+		transaction.set(
+			crate::columns::META,
+			crate::static_keys::TRACE_CACHE_BLOCKS,
+			&blocks.encode(),
+		);
+
+		self.db.commit(transaction).map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+}
+
+/// Number of consecutive blocks sharing one entry of the log index.
+pub const LOG_INDEX_BUCKET_SIZE: u64 = 256;
+
+/// A field of an Ethereum log that is indexed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Encode, Decode)]
+pub enum LogIndexKind {
+	/// The address of the contract emitting the log.
+	Address(H160),
+	/// A topic at the given position.
+	Topic(u8, H256),
+}
+
+#[derive(Encode, Decode)]
+enum LogIndexKey<Hash> {
+	/// Whether the logs of a block are indexed, or could not be.
+	Block(Hash),
+	/// Number of records of a bucket of blocks matching a field.
+	Records(LogIndexKind, u64),
+	/// Logs of one block matching a field, records of a bucket are only ever appended.
+	Record(LogIndexKind, u64, u32),
+	/// Heights of a bucket of blocks covered by the index.
+	Coverage(u64),
+}
+
+/// One bit per height of a bucket, set when a block of that height is indexed, and when the
+/// logs of a block of that height could not be read. Blocks of all forks are counted.
+#[derive(Default, Encode, Decode)]
+struct BucketCoverage {
+	indexed: [u8; 32],
+	unindexed: [u8; 32],
+}
+
+impl BucketCoverage {
+	fn bit(bits: &[u8; 32], offset: u64) -> bool {
+		bits[(offset / 8) as usize] & (1 << (offset % 8)) != 0
+	}
+
+	fn set_bit(bits: &mut [u8; 32], offset: u64) {
+		bits[(offset / 8) as usize] |= 1 << (offset % 8);
+	}
+}
+
+/// Address and topics of a log, in the order of the block.
+pub struct IndexedLog {
+	pub transaction_index: u32,
+	pub address: H160,
+	pub topics: Vec<H256>,
+}
+
+pub struct LogCommitment<Block: BlockT> {
+	pub block_hash: Block::Hash,
+	pub block_number: u64,
+	pub logs: Vec<IndexedLog>,
+}
+
+/// Position of a log in a block.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct LogIndexEntry<Block: BlockT> {
+	pub block_number: u64,
+	pub block_hash: Block::Hash,
+	pub transaction_index: u32,
+	pub log_index: u32,
+}
+
+/// Index of the logs of every synced block, from address and topics to their positions.
+/// Blocks of all forks are indexed, callers check that the returned blocks are canonical.
+pub struct LogDb<Block: BlockT> {
+	db: Arc<dyn Database<DbHash>>,
+	write_lock: Arc<Mutex<()>>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> LogDb<Block> {
+	/// Whether the logs of the block are indexed. Blocks that are not synced yet, or whose logs
+	/// could not be read, are not.
+	pub fn is_indexed(
+		&self,
+		block_hash: &Block::Hash,
+	) -> Result<bool, String> {
+		let key = LogIndexKey::Block(*block_hash).encode();
+		match self.db.get(crate::columns::LOG_INDEX, &key) {
+			Some(raw) => Ok(bool::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?),
+			None => Ok(false),
+		}
+	}
+
+	fn records(
+		&self,
+		kind: LogIndexKind,
+		bucket: u64,
+	) -> Result<u32, String> {
+		let key = LogIndexKey::<Block::Hash>::Records(kind, bucket).encode();
+		match self.db.get(crate::columns::LOG_INDEX, &key) {
+			Some(raw) => Ok(u32::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?),
+			None => Ok(0),
+		}
+	}
+
+	fn coverage(
+		&self,
+		bucket: u64,
+	) -> Result<BucketCoverage, String> {
+		let key = LogIndexKey::<Block::Hash>::Coverage(bucket).encode();
+		match self.db.get(crate::columns::LOG_INDEX, &key) {
+			Some(raw) => Ok(BucketCoverage::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?),
+			None => Ok(BucketCoverage::default()),
+		}
+	}
+
+	/// Whether every height between `from` and `to` has an indexed block, and none has a block
+	/// whose logs could not be read. Reads one entry per bucket of the range. Blocks of all
+	/// forks are counted, callers check the canonical chain.
+	pub fn is_range_indexed(
+		&self,
+		from: u64,
+		to: u64,
+	) -> Result<bool, String> {
+		for bucket in (from / LOG_INDEX_BUCKET_SIZE)..=(to / LOG_INDEX_BUCKET_SIZE) {
+			let coverage = self.coverage(bucket)?;
+			let start = bucket * LOG_INDEX_BUCKET_SIZE;
+			let first = from.max(start) - start;
+			let last = to.min(start + LOG_INDEX_BUCKET_SIZE - 1) - start;
+			let is_covered = (first..=last).all(|offset| {
+				BucketCoverage::bit(&coverage.indexed, offset)
+					&& !BucketCoverage::bit(&coverage.unindexed, offset)
+			});
+			if !is_covered {
+				return Ok(false);
+			}
+		}
+		Ok(true)
+	}
+
+	pub fn entries(
+		&self,
+		kind: LogIndexKind,
+		bucket: u64,
+	) -> Result<Vec<LogIndexEntry<Block>>, String> {
+		let mut ret = Vec::new();
+		for record in 0..self.records(kind, bucket)? {
+			let key = LogIndexKey::<Block::Hash>::Record(kind, bucket, record).encode();
+			if let Some(raw) = self.db.get(crate::columns::LOG_INDEX, &key) {
+				ret.extend(
+					Vec::<LogIndexEntry<Block>>::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?
+				);
+			}
+		}
+		Ok(ret)
+	}
+
+	/// Return the positions of the logs between blocks `from` and `to` emitted by one of
+	/// `addresses` and having, at every position of `topics`, one of the given topics. Empty
+	/// `addresses` or positions of `topics` match anything, at least one must be restricted.
+	/// Entries are ordered by block number and log index.
+	pub fn query(
+		&self,
+		addresses: &[H160],
+		topics: &[Vec<H256>],
+		from: u64,
+		to: u64,
+	) -> Result<Vec<LogIndexEntry<Block>>, String> {
+		let mut constraints: Vec<Vec<LogIndexKind>> = Vec::new();
+		if !addresses.is_empty() {
+			constraints.push(addresses.iter().map(|address| LogIndexKind::Address(*address)).collect());
+		}
+		for (position, alternatives) in topics.iter().enumerate().take(4) {
+			if !alternatives.is_empty() {
+				constraints.push(
+					alternatives.iter().map(|topic| LogIndexKind::Topic(position as u8, *topic)).collect()
+				);
+			}
+		}
+		if constraints.is_empty() {
+			return Err("Log index query is not restricted".to_string());
+		}
+
+		let mut ret = Vec::new();
+		for bucket in (from / LOG_INDEX_BUCKET_SIZE)..=(to / LOG_INDEX_BUCKET_SIZE) {
+			let mut matching: Option<BTreeMap<(u64, Block::Hash, u32), LogIndexEntry<Block>>> = None;
+			for alternatives in constraints.iter() {
+				let mut found = BTreeMap::new();
+				for kind in alternatives {
+					for entry in self.entries(*kind, bucket)? {
+						if entry.block_number >= from && entry.block_number <= to {
+							found.insert((entry.block_number, entry.block_hash, entry.log_index), entry);
+						}
+					}
+				}
+				matching = Some(match matching {
+					Some(matching) => matching.into_iter()
+						.filter(|(key, _)| found.contains_key(key))
+						.collect(),
+					None => found,
+				});
+			}
+			ret.extend(matching.unwrap_or_default().into_iter().map(|(_, entry)| entry));
+		}
+
+		Ok(ret)
+	}
+
+	/// Index the logs of a block. Each field matched by the block appends one record to its
+	/// bucket, a block that is already indexed is left untouched.
+	pub fn write_logs(
+		&self,
+		commitment: LogCommitment<Block>,
+	) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		if self.is_indexed(&commitment.block_hash)? {
+			return Ok(());
+		}
+
+		let bucket = commitment.block_number / LOG_INDEX_BUCKET_SIZE;
+		let mut records: BTreeMap<LogIndexKind, Vec<LogIndexEntry<Block>>> = BTreeMap::new();
+		for (log_index, log) in commitment.logs.iter().enumerate() {
+			let entry = LogIndexEntry::<Block> {
+				block_number: commitment.block_number,
+				block_hash: commitment.block_hash,
+				transaction_index: log.transaction_index,
+				log_index: log_index as u32,
+			};
+			let mut kinds = BTreeSet::new();
+			kinds.insert(LogIndexKind::Address(log.address));
+			for (position, topic) in log.topics.iter().enumerate().take(4) {
+				kinds.insert(LogIndexKind::Topic(position as u8, *topic));
+			}
+			for kind in kinds {
+				records.entry(kind).or_insert_with(Vec::new).push(entry.clone());
+			}
+		}
+
+		let mut transaction = sp_database::Transaction::new();
+
+		for (kind, entries) in records {
+			let record = self.records(kind, bucket)?;
+			transaction.set(
+				crate::columns::LOG_INDEX,
+				&LogIndexKey::<Block::Hash>::Record(kind, bucket, record).encode(),
+				&entries.encode(),
+			);
+			transaction.set(
+				crate::columns::LOG_INDEX,
+				&LogIndexKey::<Block::Hash>::Records(kind, bucket).encode(),
+				&(record + 1).encode(),
+			);
+		}
+
+		let mut coverage = self.coverage(bucket)?;
+		BucketCoverage::set_bit(&mut coverage.indexed, commitment.block_number % LOG_INDEX_BUCKET_SIZE);
+		transaction.set(
+			crate::columns::LOG_INDEX,
+			&LogIndexKey::<Block::Hash>::Coverage(bucket).encode(),
+			&coverage.encode(),
+		);
+
+		transaction.set(
+			crate::columns::LOG_INDEX,
+			&LogIndexKey::Block(commitment.block_hash).encode(),
+			&true.encode(),
+		);
+
+		self.db.commit(transaction).map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+
+	/// Mark a block whose logs could not be read, log queries covering it scan blocks instead.
+	pub fn write_unindexed(
+		&self,
+		block_hash: &Block::Hash,
+		block_number: u64,
+	) -> Result<(), String> {
+		let _lock = self.write_lock.lock();
+
+		if self.is_indexed(block_hash)? {
+			return Ok(());
+		}
+
+		let mut transaction = sp_database::Transaction::new();
+
+		let bucket = block_number / LOG_INDEX_BUCKET_SIZE;
+		let mut coverage = self.coverage(bucket)?;
+		BucketCoverage::set_bit(&mut coverage.unindexed, block_number % LOG_INDEX_BUCKET_SIZE);
+		transaction.set(
+			crate::columns::LOG_INDEX,
+			&LogIndexKey::<Block::Hash>::Coverage(bucket).encode(),
+			&coverage.encode(),
+		);
+
+		transaction.set(
+			crate::columns::LOG_INDEX,
+			&LogIndexKey::Block(*block_hash).encode(),
+			&false.encode(),
+		);
+		self.db.commit(transaction).map_err(|e| format!("{:?}", e))?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_runtime::{generic, traits::BlakeTwo256, OpaqueExtrinsic};
+
+	type Block = generic::Block<generic::Header<u64, BlakeTwo256>, OpaqueExtrinsic>;
+
+	fn backend() -> (tempfile::TempDir, Backend<Block>) {
+		let dir = tempfile::tempdir().unwrap();
+		let backend = Backend::new(&DatabaseSettings {
+			source: DatabaseSettingsSrc::RocksDb { path: dir.path().to_path_buf(), cache_size: 0 },
+		}).unwrap();
+		(dir, backend)
+	}
+
+	fn parity_db_backend(path: &Path) -> Backend<Block> {
+		Backend::new(&DatabaseSettings {
+			source: DatabaseSettingsSrc::ParityDb { path: path.to_path_buf() },
+		}).expect("parity database opens")
+	}
+
+	#[test]
+	fn parity_db_backend_persists_the_mapping() {
+		let dir = tempfile::tempdir().unwrap();
+		let block_hash = H256::repeat_byte(1);
+		let ethereum_block_hash = H256::repeat_byte(2);
+		let transaction_hash = H256::repeat_byte(3);
+		{
+			let backend = parity_db_backend(dir.path());
+			backend.mapping().write_hashes(MappingCommitment {
+				block_hash,
+				ethereum_block_hash,
+				ethereum_transaction_hashes: vec![transaction_hash],
+			}).unwrap();
+			backend.meta().write_current_syncing_tips(vec![block_hash]).unwrap();
+		}
+
+		let backend = parity_db_backend(dir.path());
+		assert!(backend.mapping().is_synced(&block_hash).unwrap());
+		assert_eq!(backend.mapping().block_hash(&ethereum_block_hash).unwrap(), Some(block_hash));
+		assert_eq!(backend.mapping().transaction_metadata(&transaction_hash).unwrap().len(), 1);
+		assert_eq!(backend.meta().current_syncing_tips().unwrap(), vec![block_hash]);
+	}
+
+	fn parity_db_adapter(path: &Path) -> parity_db_adapter::DbAdapter {
+		let db = parity_db::Db::open(
+			&parity_db::Options::with_columns(path, crate::columns::NUM_COLUMNS as u8)
+		).expect("parity database opens");
+		parity_db_adapter::DbAdapter(db)
+	}
+
+	#[test]
+	fn parity_db_adapter_sets_and_removes_values() {
+		let dir = tempfile::tempdir().unwrap();
+		let db = parity_db_adapter(dir.path());
+
+		let mut transaction = sp_database::Transaction::<DbHash>::new();
+		transaction.set(crate::columns::META, b"key", b"value");
+		Database::<DbHash>::commit(&db, transaction).unwrap();
+		assert_eq!(Database::<DbHash>::get(&db, crate::columns::META, b"key"), Some(b"value".to_vec()));
+
+		let mut transaction = sp_database::Transaction::<DbHash>::new();
+		transaction.remove(crate::columns::META, b"key");
+		Database::<DbHash>::commit(&db, transaction).unwrap();
+		assert_eq!(Database::<DbHash>::get(&db, crate::columns::META, b"key"), None);
+	}
+
+	#[test]
+	fn parity_db_adapter_rejects_content_addressed_changes() {
+		let dir = tempfile::tempdir().unwrap();
+		let db = parity_db_adapter(dir.path());
+
+		let mut transaction = sp_database::Transaction::<DbHash>::new();
+		transaction.set(crate::columns::META, b"key", b"value");
+		transaction.0.push(sp_database::Change::Release(DbHash::default()));
+		assert!(Database::<DbHash>::commit(&db, transaction).is_err());
+		// The rejected transaction is not partially applied.
+		assert_eq!(Database::<DbHash>::get(&db, crate::columns::META, b"key"), None);
+		assert_eq!(Database::<DbHash>::lookup(&db, &DbHash::default()), None);
+	}
+
+	#[test]
+	fn trace_cache_evicts_the_oldest_blocks() {
+		let (_dir, backend) = backend();
+		let traces = backend.traces();
+		let hash = |n: u64| H256::from_low_u64_be(n);
+
+		for n in 0..(TRACE_CACHE_MAX_BLOCKS as u64 + 2) {
+			traces.write_block_traces(&hash(n), &n).unwrap();
+		}
+
+		assert_eq!(traces.block_traces::<u64>(&hash(0)).unwrap(), None);
+		assert_eq!(traces.block_traces::<u64>(&hash(1)).unwrap(), None);
+		assert_eq!(traces.block_traces::<u64>(&hash(2)).unwrap(), Some(2));
+		let cached = traces.cached_blocks().unwrap();
+		assert_eq!(cached.len(), TRACE_CACHE_MAX_BLOCKS);
+		assert_eq!(cached.first(), Some(&hash(2)));
+	}
+
+	#[test]
+	fn trace_cache_rewrite_refreshes_the_block() {
+		let (_dir, backend) = backend();
+		let traces = backend.traces();
+		let hash = |n: u64| H256::from_low_u64_be(n);
+
+		traces.write_block_traces(&hash(0), &0u64).unwrap();
+		traces.write_block_traces(&hash(1), &1u64).unwrap();
+		traces.write_block_traces(&hash(0), &2u64).unwrap();
+
+		assert_eq!(traces.cached_blocks().unwrap(), vec![hash(1), hash(0)]);
+		assert_eq!(traces.block_traces::<u64>(&hash(0)).unwrap(), Some(2));
+	}
+
+	fn log(transaction_index: u32, address: u64, topics: &[u64]) -> IndexedLog {
+		IndexedLog {
+			transaction_index,
+			address: H160::from_low_u64_be(address),
+			topics: topics.iter().map(|topic| H256::from_low_u64_be(*topic)).collect(),
+		}
+	}
+
+	fn write_logs(backend: &Backend<Block>, block_number: u64, block_hash: H256, logs: Vec<IndexedLog>) {
+		backend.logs().write_logs(LogCommitment { block_hash, block_number, logs }).unwrap();
+	}
+
+	fn positions(entries: Vec<LogIndexEntry<Block>>) -> Vec<(u64, H256, u32)> {
+		entries.into_iter().map(|entry| (entry.block_number, entry.block_hash, entry.log_index)).collect()
+	}
+
+	#[test]
+	fn log_query_intersects_address_and_topics() {
+		let (_dir, backend) = backend();
+		let hash = H256::repeat_byte(1);
+		write_logs(&backend, 1, hash, vec![
+			log(0, 10, &[100, 200]),
+			log(0, 10, &[101, 200]),
+			log(1, 11, &[100, 200]),
+		]);
+
+		let address = H160::from_low_u64_be(10);
+		let topic = |n: u64| H256::from_low_u64_be(n);
+		assert_eq!(
+			positions(backend.logs().query(&[address], &[vec![topic(100)]], 0, 10).unwrap()),
+			vec![(1, hash, 0)],
+		);
+		// Alternatives of a position are a union.
+		assert_eq!(
+			positions(backend.logs().query(&[address], &[vec![topic(100), topic(101)]], 0, 10).unwrap()),
+			vec![(1, hash, 0), (1, hash, 1)],
+		);
+		// An empty position matches anything.
+		assert_eq!(
+			positions(backend.logs().query(&[], &[vec![], vec![topic(200)]], 0, 10).unwrap()),
+			vec![(1, hash, 0), (1, hash, 1), (1, hash, 2)],
+		);
+		assert!(backend.logs().query(&[], &[vec![]], 0, 10).is_err());
+	}
+
+	#[test]
+	fn log_query_matches_topic_positions() {
+		let (_dir, backend) = backend();
+		let hash = H256::repeat_byte(1);
+		write_logs(&backend, 1, hash, vec![log(0, 10, &[100, 200])]);
+
+		let topic = |n: u64| H256::from_low_u64_be(n);
+		assert_eq!(
+			positions(backend.logs().query(&[], &[vec![], vec![topic(200)]], 0, 10).unwrap()),
+			vec![(1, hash, 0)],
+		);
+		assert!(backend.logs().query(&[], &[vec![topic(200)]], 0, 10).unwrap().is_empty());
+	}
+
+	#[test]
+	fn log_query_returns_entries_of_every_fork() {
+		let (_dir, backend) = backend();
+		let (a1, b1) = (H256::repeat_byte(0xa1), H256::repeat_byte(0xb1));
+		write_logs(&backend, 1, a1, vec![log(0, 10, &[])]);
+		write_logs(&backend, 1, b1, vec![log(0, 10, &[])]);
+
+		let address = H160::from_low_u64_be(10);
+		let mut found = positions(backend.logs().query(&[address], &[], 0, 10).unwrap());
+		found.sort();
+		let mut expected = vec![(1, a1, 0), (1, b1, 0)];
+		expected.sort();
+		assert_eq!(found, expected);
+	}
+
+	#[test]
+	fn log_query_is_bounded_by_the_range() {
+		let (_dir, backend) = backend();
+		let address = H160::from_low_u64_be(10);
+		for number in 0..(LOG_INDEX_BUCKET_SIZE + 2) {
+			write_logs(&backend, number, H256::from_low_u64_be(number), vec![log(0, 10, &[])]);
+		}
+
+		let found = positions(backend.logs().query(
+			&[address], &[], LOG_INDEX_BUCKET_SIZE - 1, LOG_INDEX_BUCKET_SIZE,
+		).unwrap());
+		assert_eq!(found, vec![
+			(LOG_INDEX_BUCKET_SIZE - 1, H256::from_low_u64_be(LOG_INDEX_BUCKET_SIZE - 1), 0),
+			(LOG_INDEX_BUCKET_SIZE, H256::from_low_u64_be(LOG_INDEX_BUCKET_SIZE), 0),
+		]);
+	}
+
+	#[test]
+	fn write_logs_appends_to_the_bucket() {
+		let (_dir, backend) = backend();
+		let address = H160::from_low_u64_be(10);
+		let (first, second) = (H256::repeat_byte(1), H256::repeat_byte(2));
+		write_logs(&backend, 1, first, vec![log(0, 10, &[])]);
+		write_logs(&backend, 2, second, vec![log(0, 10, &[])]);
+		// Syncing a block again does not duplicate its entries.
+		write_logs(&backend, 1, first, vec![log(0, 10, &[])]);
+
+		assert_eq!(
+			positions(backend.logs().entries(LogIndexKind::Address(address), 0).unwrap()),
+			vec![(1, first, 0), (2, second, 0)],
+		);
+	}
+
+	#[test]
+	fn unindexed_blocks_are_not_indexed() {
+		let (_dir, backend) = backend();
+		let (indexed, unindexed) = (H256::repeat_byte(1), H256::repeat_byte(2));
+		write_logs(&backend, 1, indexed, Vec::new());
+		backend.logs().write_unindexed(&unindexed, 2).unwrap();
+		// The marker does not override an indexed block.
+		backend.logs().write_unindexed(&indexed, 1).unwrap();
+
+		assert!(backend.logs().is_indexed(&indexed).unwrap());
+		assert!(!backend.logs().is_indexed(&unindexed).unwrap());
+		assert!(!backend.logs().is_indexed(&H256::repeat_byte(3)).unwrap());
+	}
+	#[test]
+	fn range_coverage_is_tracked_per_bucket() {
+		let (_dir, backend) = backend();
+		let end = 2 * LOG_INDEX_BUCKET_SIZE;
+		for number in 1..end {
+			write_logs(&backend, number, H256::from_low_u64_be(number), Vec::new());
+		}
+
+		assert!(backend.logs().is_range_indexed(1, end - 1).unwrap());
+		assert!(backend.logs().is_range_indexed(LOG_INDEX_BUCKET_SIZE - 1, LOG_INDEX_BUCKET_SIZE).unwrap());
+		// Block 0 and the blocks past the end are not indexed.
+		assert!(!backend.logs().is_range_indexed(0, 10).unwrap());
+		assert!(!backend.logs().is_range_indexed(10, end).unwrap());
+
+		// A block whose logs could not be read leaves its height uncovered, even when another
+		// block of that height is indexed.
+		backend.logs().write_unindexed(&H256::repeat_byte(0xff), 300).unwrap();
+		assert!(!backend.logs().is_range_indexed(1, end - 1).unwrap());
+		assert!(backend.logs().is_range_indexed(1, 299).unwrap());
+		assert!(backend.logs().is_range_indexed(301, end - 1).unwrap());
+	}
+}
@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use sp_database::{Change, ColumnId, Transaction, error::{DatabaseError, Result}};
+use crate::Database;
+
+fn handle_err<T>(result: parity_db::Result<T>) -> T {
+	match result {
+		Ok(r) => r,
+		Err(e) => {
+			panic!("Critical database error: {:?}", e);
+		}
+	}
+}
+
+fn database_error(message: String) -> DatabaseError {
+	DatabaseError(Box::new(std::io::Error::new(std::io::ErrorKind::Other, message)))
+}
+
+/// Wrap a ParityDB database into the `Database` trait, as Substrate does for its own backend.
+/// Frontier only stores values by key, the content-addressed operations are not supported.
+pub struct DbAdapter(pub parity_db::Db);
+
+impl<H: Clone> Database<H> for DbAdapter {
+	fn commit(&self, transaction: Transaction<H>) -> Result<()> {
+		let mut changes = Vec::with_capacity(transaction.0.len());
+		for change in transaction.0 {
+			match change {
+				Change::Set(col, key, value) => changes.push((col as u8, key, Some(value))),
+				Change::Remove(col, key) => changes.push((col as u8, key, None)),
+				Change::Store(..) | Change::Release(..) =>
+					return Err(database_error("Content-addressed changes are not supported".to_string())),
+			}
+		}
+
+		self.0.commit(changes).map_err(|e| database_error(format!("{:?}", e)))
+	}
+
+	fn get(&self, col: ColumnId, key: &[u8]) -> Option<Vec<u8>> {
+		handle_err(self.0.get(col as u8, key))
+	}
+
+	fn lookup(&self, _hash: &H) -> Option<Vec<u8>> {
+		// Nothing is ever stored by hash.
+		None
+	}
+}
@@ -0,0 +1,273 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Versioning and in place migrations of the database layout.
+
+use std::collections::BTreeSet;
+use sp_runtime::traits::Block as BlockT;
+use kvdb::KeyValueDB;
+use codec::{Encode, Decode};
+use crate::{Database, DbHash, TransactionMetadata};
+
+/// Version of the database layout written by this release.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Columns of a database created before the trace cache was added.
+const BASE_NUM_COLUMNS: u32 = 4;
+
+fn stored_version(db: &dyn KeyValueDB) -> Result<Option<u32>, String> {
+	match db.get(crate::columns::META, crate::static_keys::DATABASE_VERSION).map_err(|e| format!("{}", e))? {
+		Some(raw) => Ok(Some(u32::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?)),
+		None => Ok(None),
+	}
+}
+
+fn write_version(db: &dyn KeyValueDB, version: u32) -> Result<(), String> {
+	let mut transaction = db.transaction();
+	transaction.put(crate::columns::META, crate::static_keys::DATABASE_VERSION, &version.encode());
+	db.write(transaction).map_err(|e| format!("{}", e))
+}
+
+/// Open a RocksDB database, adding the columns it lacks and migrating it to the current version.
+///
+/// Databases without a stored version predate versioning and are of version 1, whatever their
+/// columns.
+pub(crate) fn open_rocksdb<Block: BlockT>(path: &str) -> Result<kvdb_rocksdb::Database, String> {
+	let db_config = kvdb_rocksdb::DatabaseConfig::with_columns(crate::columns::NUM_COLUMNS);
+	let mut db = match kvdb_rocksdb::Database::open(&db_config, path) {
+		Ok(db) => db,
+		// RocksDB refuses to open a database without all of its columns, which is the case of a
+		// database created before columns were added.
+		Err(err) => (BASE_NUM_COLUMNS..crate::columns::NUM_COLUMNS).rev()
+			.find_map(|num_columns| kvdb_rocksdb::Database::open(
+				&kvdb_rocksdb::DatabaseConfig::with_columns(num_columns),
+				path,
+			).ok())
+			.ok_or_else(|| format!("{}", err))?,
+	};
+
+	let version = stored_version(&db)?.unwrap_or(1);
+	if version > CURRENT_VERSION {
+		return Err(format!("Future Frontier database version: {}", version))
+	}
+
+	// Columns are added one at a time, an interrupted upgrade adds the missing ones.
+	while db.num_columns() < crate::columns::NUM_COLUMNS {
+		db.add_column().map_err(|e| format!("{}", e))?;
+	}
+
+	// Each step is recorded as soon as it is done, an interrupted upgrade resumes from there.
+	for from in version..CURRENT_VERSION {
+		match from {
+			1 => migrate_1_to_2::<Block>(&db)?,
+			_ => return Err(format!("No migration from Frontier database version {}", from)),
+		}
+		write_version(&db, from + 1)?;
+	}
+
+	Ok(db)
+}
+
+/// Version 2 keeps a single transaction candidate per block in the transaction mapping.
+fn migrate_1_to_2<Block: BlockT>(db: &kvdb_rocksdb::Database) -> Result<(), String> {
+	let mut transaction = db.transaction();
+	for (key, value) in db.iter(crate::columns::TRANSACTION_MAPPING) {
+		let metadata = Vec::<TransactionMetadata<Block>>::decode(&mut &value[..])
+			.map_err(|e| format!("{:?}", e))?;
+
+		// The latest candidate of a block is the one it was last synced with.
+		let mut seen = BTreeSet::new();
+		let mut deduplicated: Vec<_> = metadata.iter().rev()
+			.filter(|meta| seen.insert(meta.block_hash))
+			.cloned()
+			.collect();
+		deduplicated.reverse();
+
+		if deduplicated.len() != metadata.len() {
+			transaction.put(crate::columns::TRANSACTION_MAPPING, &key, &deduplicated.encode());
+		}
+	}
+	db.write(transaction).map_err(|e| format!("{}", e))
+}
+
+/// Check the stored version of a database, recording the current one in a new database. Only
+/// RocksDB databases predate versioning, those of other sources are created versioned.
+pub(crate) fn check_version(db: &dyn Database<DbHash>) -> Result<(), String> {
+	let version = match db.get(crate::columns::META, crate::static_keys::DATABASE_VERSION) {
+		Some(raw) => u32::decode(&mut &raw[..]).map_err(|e| format!("{:?}", e))?,
+		None => {
+			let mut transaction = sp_database::Transaction::new();
+			transaction.set(crate::columns::META, crate::static_keys::DATABASE_VERSION, &CURRENT_VERSION.encode());
+			db.commit(transaction).map_err(|e| format!("{:?}", e))?;
+			CURRENT_VERSION
+		},
+	};
+
+	if version > CURRENT_VERSION {
+		Err(format!("Future Frontier database version: {}", version))
+	} else if version < CURRENT_VERSION {
+		Err(format!("No migration from Frontier database version {} for this database source", version))
+	} else {
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use sp_runtime::{generic, traits::BlakeTwo256, OpaqueExtrinsic};
+	use crate::{Backend, DatabaseSettings, DatabaseSettingsSrc, LogCommitment};
+
+	type Block = generic::Block<generic::Header<u64, BlakeTwo256>, OpaqueExtrinsic>;
+
+	fn metadata(block_hash: H256, ethereum_index: u32) -> TransactionMetadata<Block> {
+		TransactionMetadata {
+			block_hash,
+			ethereum_block_hash: H256::repeat_byte(0xee),
+			ethereum_index,
+		}
+	}
+
+	/// Write a database the way releases before the trace cache did: four columns and no version.
+	fn create_base_database(path: &str, transaction_hash: H256, candidates: &[TransactionMetadata<Block>]) {
+		let db = kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(BASE_NUM_COLUMNS),
+			path,
+		).unwrap();
+		let mut transaction = db.transaction();
+		transaction.put(
+			crate::columns::TRANSACTION_MAPPING,
+			&transaction_hash.encode(),
+			&candidates.to_vec().encode(),
+		);
+		transaction.put(crate::columns::SYNCED_MAPPING, &H256::repeat_byte(1).encode(), &true.encode());
+		db.write(transaction).unwrap();
+	}
+
+	fn open_backend(path: &std::path::Path) -> Result<Backend<Block>, String> {
+		Backend::new(&DatabaseSettings {
+			source: DatabaseSettingsSrc::RocksDb { path: path.to_path_buf(), cache_size: 0 },
+		})
+	}
+
+	#[test]
+	fn base_database_gets_the_new_columns() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().to_str().unwrap();
+		let block_hash = H256::repeat_byte(1);
+		create_base_database(path, H256::repeat_byte(0x11), &[metadata(block_hash, 0)]);
+
+		let backend = open_backend(dir.path()).unwrap();
+		assert!(backend.mapping().is_synced(&block_hash).unwrap());
+		backend.traces().write_block_traces(&block_hash, &1u64).unwrap();
+		backend.logs().write_logs(LogCommitment { block_hash, block_number: 1, logs: Vec::new() }).unwrap();
+		drop(backend);
+
+		let db = open_rocksdb::<Block>(path).unwrap();
+		assert_eq!(db.num_columns(), crate::columns::NUM_COLUMNS);
+		drop(db);
+
+		let backend = open_backend(dir.path()).unwrap();
+		assert_eq!(backend.traces().block_traces::<u64>(&block_hash).unwrap(), Some(1));
+		assert!(backend.logs().is_indexed(&block_hash).unwrap());
+	}
+
+	#[test]
+	fn unversioned_database_is_migrated() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().to_str().unwrap();
+		let (a1, b1) = (H256::repeat_byte(1), H256::repeat_byte(2));
+		let transaction_hash = H256::repeat_byte(0x11);
+		// A1 was synced twice, its latest candidate is kept.
+		create_base_database(path, transaction_hash, &[metadata(a1, 0), metadata(b1, 1), metadata(a1, 2)]);
+
+		let backend = open_backend(dir.path()).unwrap();
+		let candidates: Vec<(H256, u32)> = backend.mapping().transaction_metadata(&transaction_hash).unwrap()
+			.into_iter()
+			.map(|meta| (meta.block_hash, meta.ethereum_index))
+			.collect();
+		assert_eq!(candidates, vec![(b1, 1), (a1, 2)]);
+		drop(backend);
+
+		let db = open_rocksdb::<Block>(path).unwrap();
+		assert_eq!(stored_version(&db).unwrap(), Some(CURRENT_VERSION));
+	}
+
+	#[test]
+	fn unversioned_database_with_all_columns_is_migrated() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().to_str().unwrap();
+		let a1 = H256::repeat_byte(1);
+		let transaction_hash = H256::repeat_byte(0x11);
+		// Created with the current columns, by a release before versioning.
+		{
+			let db = kvdb_rocksdb::Database::open(
+				&kvdb_rocksdb::DatabaseConfig::with_columns(crate::columns::NUM_COLUMNS),
+				path,
+			).unwrap();
+			let mut transaction = db.transaction();
+			transaction.put(
+				crate::columns::TRANSACTION_MAPPING,
+				&transaction_hash.encode(),
+				&vec![metadata(a1, 0), metadata(a1, 1)].encode(),
+			);
+			db.write(transaction).unwrap();
+		}
+
+		let backend = open_backend(dir.path()).unwrap();
+		let candidates: Vec<u32> = backend.mapping().transaction_metadata(&transaction_hash).unwrap()
+			.into_iter()
+			.map(|meta| meta.ethereum_index)
+			.collect();
+		assert_eq!(candidates, vec![1]);
+	}
+
+	#[test]
+	fn interrupted_migration_resumes() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().to_str().unwrap();
+		create_base_database(path, H256::repeat_byte(0x11), &[metadata(H256::repeat_byte(1), 0)]);
+
+		// The version was recorded and a single column added when the upgrade stopped.
+		{
+			let mut db = kvdb_rocksdb::Database::open(
+				&kvdb_rocksdb::DatabaseConfig::with_columns(BASE_NUM_COLUMNS),
+				path,
+			).unwrap();
+			write_version(&db, 1).unwrap();
+			db.add_column().unwrap();
+		}
+
+		let db = open_rocksdb::<Block>(path).unwrap();
+		assert_eq!(db.num_columns(), crate::columns::NUM_COLUMNS);
+		assert_eq!(stored_version(&db).unwrap(), Some(CURRENT_VERSION));
+	}
+
+	#[test]
+	fn future_version_is_refused() {
+		let dir = tempfile::tempdir().unwrap();
+		let path = dir.path().to_str().unwrap();
+		{
+			let db = open_rocksdb::<Block>(path).unwrap();
+			write_version(&db, CURRENT_VERSION + 1).unwrap();
+		}
+
+		assert!(open_backend(dir.path()).is_err());
+	}
+}
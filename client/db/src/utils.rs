@@ -17,22 +17,34 @@
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
 use std::sync::Arc;
+use sp_runtime::traits::Block as BlockT;
 use crate::{Database, DbHash, DatabaseSettings, DatabaseSettingsSrc};
 
-pub fn open_database(
+pub fn open_database<Block: BlockT>(
 	config: &DatabaseSettings,
 ) -> Result<Arc<dyn Database<DbHash>>, String> {
 	let db: Arc<dyn Database<DbHash>> = match &config.source {
 		DatabaseSettingsSrc::RocksDb { path, cache_size: _ } => {
-			let db_config = kvdb_rocksdb::DatabaseConfig::with_columns(crate::columns::NUM_COLUMNS);
 			let path = path.to_str()
 				.ok_or_else(|| "Invalid database path".to_string())?;
 
-			let db = kvdb_rocksdb::Database::open(&db_config, &path)
-				.map_err(|err| format!("{}", err))?;
+			let db = crate::upgrade::open_rocksdb::<Block>(&path)?;
 			sp_database::as_database(db)
-		}
+		},
+		DatabaseSettingsSrc::ParityDb { path } => {
+			let mut db_config = parity_db::Options::with_columns(path, crate::columns::NUM_COLUMNS as u8);
+			db_config.sync = true;
+
+			let db = parity_db::Db::open(&db_config)
+				.map_err(|err| format!("{:?}", err))?;
+			Arc::new(crate::parity_db_adapter::DbAdapter(db))
+		},
+		DatabaseSettingsSrc::InMemory => {
+			let db = kvdb_memorydb::create(crate::columns::NUM_COLUMNS);
+			sp_database::as_database(db)
+		},
 	};
+	crate::upgrade::check_version(db.as_ref())?;
 
 	Ok(db)
 }
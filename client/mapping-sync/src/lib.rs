@@ -20,21 +20,65 @@ mod worker;
 
 pub use worker::MappingSyncWorker;
 
-use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT, Zero}};
+use std::sync::{Arc, Mutex};
+use sp_runtime::{generic::BlockId, traits::{Block as BlockT, Header as HeaderT, Zero, One, UniqueSaturatedInto, NumberFor}};
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use sc_client_api::BlockOf;
 use sp_blockchain::HeaderBackend;
 use fp_rpc::EthereumRuntimeRPCApi;
 use fp_consensus::FindLogError;
 
-pub fn sync_block<Block: BlockT>(
+/// Index the logs of a block. Blocks whose state is no longer available are marked unindexed,
+/// log queries covering them fall back to scanning blocks.
+pub fn sync_block_logs<Block: BlockT, C>(
+	client: &C,
 	backend: &fc_db::Backend<Block>,
 	header: &Block::Header,
-) -> Result<(), String> {
+	has_ethereum_block: bool,
+) -> Result<(), String> where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
+	let block_number = UniqueSaturatedInto::<u64>::unique_saturated_into(*header.number());
+	let statuses = if has_ethereum_block {
+		match client.runtime_api().current_transaction_statuses(&BlockId::Hash(header.hash())) {
+			Ok(Some(statuses)) => statuses,
+			_ => return backend.logs().write_unindexed(&header.hash(), block_number),
+		}
+	} else {
+		Vec::new()
+	};
+
+	let logs = statuses.into_iter().flat_map(|status| {
+		let transaction_index = status.transaction_index;
+		status.logs.into_iter().map(move |log| fc_db::IndexedLog {
+			transaction_index,
+			address: log.address,
+			topics: log.topics,
+		})
+	}).collect();
+
+	backend.logs().write_logs(fc_db::LogCommitment {
+		block_hash: header.hash(),
+		block_number,
+		logs,
+	})
+}
+
+pub fn sync_block<Block: BlockT, C>(
+	client: &C,
+	backend: &fc_db::Backend<Block>,
+	header: &Block::Header,
+) -> Result<(), String> where
+	C: ProvideRuntimeApi<Block> + Send + Sync + HeaderBackend<Block> + BlockOf,
+	C::Api: EthereumRuntimeRPCApi<Block>,
+{
 	match fp_consensus::find_log(header.digest()) {
 		Ok(log) => {
 			let post_hashes = log.into_hashes();
 
+			sync_block_logs(client, backend, header, true)?;
+
 			let mapping_commitment = fc_db::MappingCommitment {
 				block_hash: header.hash(),
 				ethereum_block_hash: post_hashes.block_hash,
@@ -45,6 +89,8 @@ pub fn sync_block<Block: BlockT>(
 			Ok(())
 		},
 		Err(FindLogError::NotFound) => {
+			sync_block_logs(client, backend, header, false)?;
+
 			backend.mapping().write_none(header.hash())?;
 
 			Ok(())
@@ -76,8 +122,10 @@ pub fn sync_genesis_block<Block: BlockT, C>(
 			ethereum_block_hash: block_hash,
 			ethereum_transaction_hashes: Vec::new(),
 		};
+		sync_block_logs(client, backend, header, false)?;
 		backend.mapping().write_hashes(mapping_commitment)?;
 	} else {
+		sync_block_logs(client, backend, header, false)?;
 		backend.mapping().write_none(header.hash())?;
 	}
 
@@ -131,7 +179,7 @@ pub fn sync_one_block<Block: BlockT, C, B>(
 		frontier_backend.meta().write_current_syncing_tips(current_syncing_tips)?;
 		Ok(true)
 	} else {
-		sync_block(frontier_backend, &operating_header)?;
+		sync_block(client, frontier_backend, &operating_header)?;
 
 		current_syncing_tips.push(*operating_header.parent_hash());
 		frontier_backend.meta().write_current_syncing_tips(current_syncing_tips)?;
@@ -157,3 +205,85 @@ pub fn sync_blocks<Block: BlockT, C, B>(
 
 	Ok(synced_any)
 }
+
+/// Canonical blocks looked at, at most, when measuring the mapping lag.
+pub const MAX_LAG_SCAN: u32 = 4096;
+
+/// How far the mapping is behind the canonical chain.
+#[derive(Clone)]
+pub struct MappingLag<Block: BlockT> {
+	/// Latest canonical block whose mapping is synced, if among the scanned blocks.
+	pub latest_mapped: Option<(NumberFor<Block>, Block::Hash)>,
+	/// Canonical blocks whose mapping is not synced yet, among the scanned blocks.
+	pub unmapped_blocks: u32,
+	/// Whether the scan stopped after `MAX_LAG_SCAN` blocks, `unmapped_blocks` is then a
+	/// lower bound.
+	pub capped: bool,
+}
+
+/// Mapping lag last measured by the `MappingSyncWorker`, shared with the RPC.
+pub type SharedMappingLag<Block> = Arc<Mutex<Option<MappingLag<Block>>>>;
+
+/// Measure the mapping lag by walking the canonical chain down from the best block. Once the
+/// syncing tips are exhausted the blocks below the latest mapped one are synced, otherwise up to
+/// `MAX_LAG_SCAN` blocks are scanned.
+pub fn mapping_lag<Block: BlockT, C>(
+	client: &C,
+	frontier_backend: &fc_db::Backend<Block>,
+) -> Result<MappingLag<Block>, String> where
+	C: HeaderBackend<Block>,
+{
+	let syncing_tips = frontier_backend.meta().current_syncing_tips()?;
+
+	let mut latest_mapped = None;
+	let mut unmapped_blocks = 0;
+	let mut capped = true;
+	let mut number = client.info().best_number;
+	for _ in 0..MAX_LAG_SCAN {
+		let hash = client.hash(number)
+			.map_err(|e| format!("{:?}", e))?
+			.ok_or("Canonical block not found".to_string())?;
+
+		if frontier_backend.mapping().is_synced(&hash)? {
+			if latest_mapped.is_none() {
+				latest_mapped = Some((number, hash));
+			}
+			if syncing_tips.is_empty() {
+				capped = false;
+				break
+			}
+		} else {
+			unmapped_blocks += 1;
+		}
+
+		if number.is_zero() {
+			capped = false;
+			break
+		}
+		number -= One::one();
+	}
+
+	Ok(MappingLag { latest_mapped, unmapped_blocks, capped })
+}
+
+/// The mapping lag last measured by the `MappingSyncWorker`, measured now if the worker did not
+/// share it yet.
+pub fn cached_mapping_lag<Block: BlockT, C>(
+	client: &C,
+	frontier_backend: &fc_db::Backend<Block>,
+	shared: &SharedMappingLag<Block>,
+) -> Result<MappingLag<Block>, String> where
+	C: HeaderBackend<Block>,
+{
+	let cached = shared.lock().map_err(|_| "Mapping lag is not available".to_string())?.clone();
+	match cached {
+		Some(lag) => Ok(lag),
+		None => {
+			let lag = mapping_lag(client, frontier_backend)?;
+			if let Ok(mut shared) = shared.lock() {
+				shared.get_or_insert_with(|| lag.clone());
+			}
+			Ok(lag)
+		},
+	}
+}
@@ -16,21 +16,49 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::pin::Pin;
 use std::sync::Arc;
 use futures::{prelude::*, task::{Context, Poll}};
-use sp_runtime::traits::Block as BlockT;
+use sp_runtime::traits::{Block as BlockT, UniqueSaturatedInto};
 use sc_client_api::ImportNotifications;
 use sp_api::ProvideRuntimeApi;
 use sc_client_api::BlockOf;
 use sp_blockchain::HeaderBackend;
 use fp_rpc::EthereumRuntimeRPCApi;
 use futures_timer::Delay;
-use log::debug;
+use log::{debug, warn};
+use prometheus_endpoint::{register, Gauge, PrometheusError, Registry, U64};
 
 const LIMIT: usize = 8;
 
+/// Mapping sync metrics.
+struct Metrics {
+	latest_mapped_block: Gauge<U64>,
+	unmapped_blocks: Gauge<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			latest_mapped_block: register(
+				Gauge::new(
+					"frontier_mapping_sync_latest_mapped_block",
+					"Number of the latest canonical block whose mapping is synced",
+				)?,
+				registry,
+			)?,
+			unmapped_blocks: register(
+				Gauge::new(
+					"frontier_mapping_sync_unmapped_blocks",
+					"Number of canonical blocks whose mapping is not synced yet",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
 pub struct MappingSyncWorker<Block: BlockT, C, B> {
 	import_notifications: ImportNotifications<Block>,
 	timeout: Duration,
@@ -41,6 +69,10 @@ pub struct MappingSyncWorker<Block: BlockT, C, B> {
 	frontier_backend: Arc<fc_db::Backend<Block>>,
 
 	have_next: bool,
+
+	mapping_lag: crate::SharedMappingLag<Block>,
+	mapping_lag_updated_at: Option<Instant>,
+	metrics: Option<Metrics>,
 }
 
 impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
@@ -50,7 +82,17 @@ impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
 		client: Arc<C>,
 		substrate_backend: Arc<B>,
 		frontier_backend: Arc<fc_db::Backend<Block>>,
+		mapping_lag: crate::SharedMappingLag<Block>,
+		prometheus_registry: Option<&Registry>,
 	) -> Self {
+		let metrics = prometheus_registry.and_then(|registry| match Metrics::register(registry) {
+			Ok(metrics) => Some(metrics),
+			Err(e) => {
+				warn!(target: "mapping-sync", "Failed to register metrics: {:?}", e);
+				None
+			},
+		});
+
 		Self {
 			import_notifications,
 			timeout,
@@ -61,6 +103,41 @@ impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> {
 			frontier_backend,
 
 			have_next: true,
+
+			mapping_lag,
+			mapping_lag_updated_at: None,
+			metrics,
+		}
+	}
+}
+
+impl<Block: BlockT, C, B> MappingSyncWorker<Block, C, B> where
+	C: HeaderBackend<Block>,
+{
+	/// Measure the mapping lag and share it with the RPC and the metrics, at most once per
+	/// timeout as measuring the lag may walk many blocks.
+	fn update_mapping_lag(&mut self) {
+		if self.mapping_lag_updated_at.map_or(false, |at| at.elapsed() < self.timeout) {
+			return
+		}
+		self.mapping_lag_updated_at = Some(Instant::now());
+
+		let lag = match crate::mapping_lag(self.client.as_ref(), self.frontier_backend.as_ref()) {
+			Ok(lag) => lag,
+			Err(e) => {
+				debug!(target: "mapping-sync", "Measuring the mapping lag failed with error {:?}.", e);
+				return
+			},
+		};
+
+		if let Some(metrics) = &self.metrics {
+			if let Some((number, _)) = lag.latest_mapped {
+				metrics.latest_mapped_block.set(UniqueSaturatedInto::<u64>::unique_saturated_into(number));
+			}
+			metrics.unmapped_blocks.set(lag.unmapped_blocks as u64);
+		}
+		if let Ok(mut shared) = self.mapping_lag.lock() {
+			*shared = Some(lag);
 		}
 	}
 }
@@ -110,6 +187,7 @@ impl<Block: BlockT, C, B> Stream for MappingSyncWorker<Block, C, B> where
 			) {
 				Ok(have_next) => {
 					self.have_next = have_next;
+					self.update_mapping_lag();
 					Poll::Ready(Some(()))
 				},
 				Err(e) => {
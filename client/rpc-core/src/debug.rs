@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Debug rpc interface.
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use crate::types::{BlockNumber, CallRequest, Trace, TraceParams};
+
+pub use rpc_impl_DebugApi::gen_server::DebugApi as DebugApiServer;
+
+/// Debug rpc interface.
+#[rpc(server)]
+pub trait DebugApi {
+	/// Re-executes a transaction and returns its trace.
+	#[rpc(name = "debug_traceTransaction")]
+	fn trace_transaction(&self, _: H256, _: Option<TraceParams>) -> Result<Trace>;
+
+	/// Executes a call on top of the given block and returns its trace.
+	#[rpc(name = "debug_traceCall")]
+	fn trace_call(&self, _: CallRequest, _: Option<BlockNumber>, _: Option<TraceParams>) -> Result<Trace>;
+}
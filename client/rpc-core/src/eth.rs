@@ -18,13 +18,14 @@
 
 //! Eth rpc interface.
 
+use std::collections::BTreeMap;
 use ethereum_types::{H160, H256, H64, U256, U64};
 use jsonrpc_core::{BoxFuture, Result};
 use jsonrpc_derive::rpc;
 
 use crate::types::{
-	BlockNumber, Bytes, CallRequest, Filter, FilterChanges, Index, Log, Receipt,
-	RichBlock, SyncStatus, Transaction, Work, TransactionRequest,
+	BlockNumber, Bytes, CallRequest, CallStateOverride, EthAccount, Filter, FilterChanges, Index, Log,
+	LogCursor, LogsPage, Receipt, RichBlock, SyncStatus, Transaction, Work, TransactionRequest,
 };
 pub use rpc_impl_EthApi::gen_server::EthApi as EthApiServer;
 pub use rpc_impl_EthFilterApi::gen_server::EthFilterApi as EthFilterApiServer;
@@ -38,7 +39,7 @@ pub trait EthApi {
 
 	/// Returns an object with data about the sync status or false. (wtf?)
 	#[rpc(name = "eth_syncing")]
-	fn syncing(&self) -> Result<SyncStatus>;
+	fn syncing(&self) -> BoxFuture<SyncStatus>;
 
 	/// Returns the number of hashes per second that the node is mining with.
 	#[rpc(name = "eth_hashrate")]
@@ -78,6 +79,11 @@ pub trait EthApi {
 	#[rpc(name = "eth_getStorageAt")]
 	fn storage_at(&self, _: H160, _: U256, _: Option<BlockNumber>) -> Result<H256>;
 
+	/// Returns the account and storage values of an address, with Merkle proofs
+	/// against the state root of the block.
+	#[rpc(name = "eth_getProof")]
+	fn proof(&self, _: H160, _: Vec<U256>, _: Option<BlockNumber>) -> Result<EthAccount>;
+
 	/// Returns block with given hash.
 	#[rpc(name = "eth_getBlockByHash")]
 	fn block_by_hash(&self, _: H256, _: bool) -> Result<Option<RichBlock>>;
@@ -119,9 +125,15 @@ pub trait EthApi {
 	#[rpc(name = "eth_sendRawTransaction")]
 	fn send_raw_transaction(&self, _: Bytes) -> BoxFuture<H256>;
 
-	/// Call contract, returning the output data.
+	/// Call contract, returning the output data. The state of the given accounts
+	/// can be overridden for the duration of the call.
 	#[rpc(name = "eth_call")]
-	fn call(&self, _: CallRequest, _: Option<BlockNumber>) -> Result<Bytes>;
+	fn call(
+		&self,
+		_: CallRequest,
+		_: Option<BlockNumber>,
+		_: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<Bytes>;
 
 	/// Estimate gas needed for execution of given contract.
 	#[rpc(name = "eth_estimateGas")]
@@ -151,6 +163,10 @@ pub trait EthApi {
 	#[rpc(name = "eth_getTransactionReceipt")]
 	fn transaction_receipt(&self, _: H256) -> Result<Option<Receipt>>;
 
+	/// Returns the receipts of every transaction of a block.
+	#[rpc(name = "eth_getBlockReceipts")]
+	fn block_receipts(&self, _: BlockNumber) -> Result<Option<Vec<Receipt>>>;
+
 	/// Returns an uncles at given block and index.
 	#[rpc(name = "eth_getUncleByBlockHashAndIndex")]
 	fn uncle_by_block_hash_and_index(&self, _: H256, _: Index) -> Result<Option<RichBlock>>;
@@ -167,6 +183,11 @@ pub trait EthApi {
 	#[rpc(name = "eth_getLogs")]
 	fn logs(&self, _: Filter) -> Result<Vec<Log>>;
 
+	/// Returns a page of at most `page_size` logs matching given filter object, starting at the
+	/// cursor returned with the previous page. Logs are in ascending order.
+	#[rpc(name = "eth_getLogsPaged")]
+	fn logs_paged(&self, _: Filter, _: Option<LogCursor>, _: Option<U256>) -> Result<LogsPage>;
+
 	/// Returns the hash of the current block, the seedHash, and the boundary condition to be met.
 	#[rpc(name = "eth_getWork")]
 	fn work(&self) -> Result<Work>;
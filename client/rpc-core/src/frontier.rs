@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Frontier rpc interface.
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use crate::types::MappingSyncStatus;
+
+pub use rpc_impl_FrontierApi::gen_server::FrontierApi as FrontierApiServer;
+
+/// Frontier rpc interface.
+#[rpc(server)]
+pub trait FrontierApi {
+	/// Returns the progress of the mapping sync, which Ethereum block and transaction
+	/// lookups depend on.
+	#[rpc(name = "frontier_syncStatus", alias("frontier_health"))]
+	fn sync_status(&self) -> Result<MappingSyncStatus>;
+}
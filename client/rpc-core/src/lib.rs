@@ -18,12 +18,20 @@
 
 pub mod types;
 
+mod debug;
 mod eth;
 mod eth_pubsub;
+mod frontier;
 mod net;
+mod trace;
+mod txpool;
 mod web3;
 
+pub use debug::{DebugApi, DebugApiServer};
 pub use eth::{EthApi, EthApiServer, EthFilterApi, EthFilterApiServer};
 pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer};
+pub use frontier::{FrontierApi, FrontierApiServer};
 pub use net::{NetApi, NetApiServer};
+pub use trace::{TraceApi, TraceApiServer};
+pub use txpool::{TxPoolApi, TxPoolApiServer};
 pub use web3::{Web3Api, Web3ApiServer};
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Trace rpc interface.
+use ethereum_types::H256;
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use crate::types::{BlockNumber, LocalizedTrace, TraceFilter};
+
+pub use rpc_impl_TraceApi::gen_server::TraceApi as TraceApiServer;
+
+/// Trace rpc interface.
+#[rpc(server)]
+pub trait TraceApi {
+	/// Returns the traces of all transactions in a block.
+	#[rpc(name = "trace_block")]
+	fn block_traces(&self, _: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>>;
+
+	/// Returns the traces of a transaction.
+	#[rpc(name = "trace_transaction")]
+	fn transaction_traces(&self, _: H256) -> Result<Option<Vec<LocalizedTrace>>>;
+
+	/// Returns the traces matching a filter.
+	#[rpc(name = "trace_filter")]
+	fn filter_traces(&self, _: TraceFilter) -> Result<Vec<LocalizedTrace>>;
+}
@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Txpool rpc interface.
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+
+use crate::types::{TxPoolContent, TxPoolInspect, TxPoolStatus};
+
+pub use rpc_impl_TxPoolApi::gen_server::TxPoolApi as TxPoolApiServer;
+
+/// Txpool rpc interface.
+#[rpc(server)]
+pub trait TxPoolApi {
+	/// Returns the Ethereum transactions of the pool, pending and queued.
+	#[rpc(name = "txpool_content")]
+	fn content(&self) -> Result<TxPoolContent>;
+
+	/// Returns a textual summary of the Ethereum transactions of the pool.
+	#[rpc(name = "txpool_inspect")]
+	fn inspect(&self) -> Result<TxPoolInspect>;
+
+	/// Returns the number of pending and queued Ethereum transactions.
+	#[rpc(name = "txpool_status")]
+	fn status(&self) -> Result<TxPoolStatus>;
+}
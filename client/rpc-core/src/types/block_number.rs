@@ -39,6 +39,10 @@ pub enum BlockNumber {
 	Earliest,
 	/// Pending block (being mined)
 	Pending,
+	/// Latest finalized block
+	Finalized,
+	/// Latest block considered safe from reorgs, the finalized block under GRANDPA
+	Safe,
 }
 
 impl Default for BlockNumber {
@@ -73,6 +77,8 @@ impl Serialize for BlockNumber {
 			BlockNumber::Latest => serializer.serialize_str("latest"),
 			BlockNumber::Earliest => serializer.serialize_str("earliest"),
 			BlockNumber::Pending => serializer.serialize_str("pending"),
+			BlockNumber::Finalized => serializer.serialize_str("finalized"),
+			BlockNumber::Safe => serializer.serialize_str("safe"),
 		}
 	}
 }
@@ -83,7 +89,7 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 	type Value = BlockNumber;
 
 	fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-		write!(formatter, "a block number or 'latest', 'earliest' or 'pending'")
+		write!(formatter, "a block number or 'latest', 'earliest', 'pending', 'finalized' or 'safe'")
 	}
 
 	fn visit_map<V>(self, mut visitor: V) -> Result<Self::Value, V::Error> where V: MapAccess<'a> {
@@ -139,6 +145,12 @@ impl<'a> Visitor<'a> for BlockNumberVisitor {
 			"latest" => Ok(BlockNumber::Latest),
 			"earliest" => Ok(BlockNumber::Earliest),
 			"pending" => Ok(BlockNumber::Pending),
+			"finalized" => Ok(BlockNumber::Finalized),
+			"safe" => Ok(BlockNumber::Safe),
+			// A bare block hash, as accepted by `eth_getBlockReceipts`.
+			_ if value.starts_with("0x") && value.len() == 66 => value[2..].parse::<H256>()
+				.map(|hash| BlockNumber::Hash { hash, require_canonical: false })
+				.map_err(|e| Error::custom(format!("Invalid block hash: {}", e))),
 			_ if value.starts_with("0x") => u64::from_str_radix(&value[2..], 16).map(BlockNumber::Num).map_err(|e| {
 				Error::custom(format!("Invalid block number: {}", e))
 			}),
@@ -179,4 +191,23 @@ mod tests {
 		assert_eq!(match_block_number(bn_hex).unwrap(), 69 as u64);
 		assert_eq!(match_block_number(bn_u64).unwrap(), 420 as u64);
 	}
+
+	#[test]
+	fn block_hash_deserialize() {
+		let bn_hash: BlockNumber = serde_json::from_str(
+			r#""0x0000000000000000000000000000000000000000000000000000000000000001""#
+		).unwrap();
+
+		assert_eq!(bn_hash, BlockNumber::Hash { hash: H256::from_low_u64_be(1), require_canonical: false });
+	}
+
+	#[test]
+	fn block_tag_deserialize() {
+		let bn_finalized: BlockNumber = serde_json::from_str(r#""finalized""#).unwrap();
+		let bn_safe: BlockNumber = serde_json::from_str(r#""safe""#).unwrap();
+
+		assert_eq!(bn_finalized, BlockNumber::Finalized);
+		assert_eq!(bn_safe, BlockNumber::Safe);
+		assert_eq!(serde_json::to_string(&BlockNumber::Finalized).unwrap(), r#""finalized""#);
+	}
 }
@@ -16,8 +16,9 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use serde::Deserialize;
-use ethereum_types::{H160, U256};
+use ethereum_types::{H160, H256, U256};
 use crate::types::Bytes;
 
 /// Call request
@@ -40,3 +41,50 @@ pub struct CallRequest {
 	/// Nonce
 	pub nonce: Option<U256>,
 }
+
+/// Account state override, applied before executing `eth_call`.
+#[derive(Debug, Default, PartialEq, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct CallStateOverride {
+	/// Balance to set for the account
+	pub balance: Option<U256>,
+	/// Nonce to set for the account
+	pub nonce: Option<U256>,
+	/// Code (EVM bytecode or wasm module) to set for the account
+	pub code: Option<Bytes>,
+	/// Storage replacing the whole storage of the account
+	pub state: Option<BTreeMap<H256, H256>>,
+	/// Storage slots to override, leaving the others untouched
+	pub state_diff: Option<BTreeMap<H256, H256>>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn state_override_deserialization() {
+		let s = r#"{
+			"0x0000000000000000000000000000000000000001": {
+				"balance": "0x10",
+				"code": "0x6000",
+				"stateDiff": {
+					"0x0000000000000000000000000000000000000000000000000000000000000001":
+					"0x0000000000000000000000000000000000000000000000000000000000000002"
+				}
+			}
+		}"#;
+		let deserialized: BTreeMap<H160, CallStateOverride> = serde_json::from_str(s).unwrap();
+		let state_override = &deserialized[&H160::from_low_u64_be(1)];
+
+		assert_eq!(state_override.balance, Some(U256::from(16)));
+		assert_eq!(state_override.nonce, None);
+		assert_eq!(state_override.code, Some(Bytes(vec![0x60, 0x00])));
+		assert_eq!(state_override.state, None);
+		assert_eq!(
+			state_override.state_diff.as_ref().and_then(|diff| diff.get(&H256::from_low_u64_be(1))),
+			Some(&H256::from_low_u64_be(2)),
+		);
+	}
+}
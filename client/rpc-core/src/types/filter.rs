@@ -392,8 +392,11 @@ impl Serialize for FilterChanges {
 #[derive(Debug, Clone)]
 pub enum FilterType {
 	Block,
-	PendingTransaction,
-	Log(Filter)
+	/// Hashes of the Ethereum transactions imported into the pool since the last poll, in
+	/// arrival order.
+	PendingTransaction(Vec<H256>),
+	/// A log filter and the hash of the best substrate block at the last poll.
+	Log(Filter, H256),
 }
 
 #[derive(Debug, Clone)]
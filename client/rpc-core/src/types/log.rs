@@ -16,7 +16,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use serde::Serialize;
+use serde::{Serialize, Deserialize};
 use ethereum_types::{H160, H256, U256};
 use crate::types::Bytes;
 
@@ -46,3 +46,40 @@ pub struct Log {
 	#[serde(default)]
 	pub removed: bool,
 }
+
+/// Position in the chain from which a paged log query resumes.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogCursor {
+	/// Block Number
+	pub block_number: U256,
+	/// Log Index in Block
+	pub log_index: U256,
+}
+
+/// A page of the logs matching a filter.
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogsPage {
+	/// Logs
+	pub logs: Vec<Log>,
+	/// Where the next page starts, none when the range is exhausted
+	pub cursor: Option<LogCursor>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json;
+
+	#[test]
+	fn log_cursor_round_trip() {
+		let cursor = LogCursor {
+			block_number: U256::from(0x1b4),
+			log_index: U256::from(2),
+		};
+		let serialized = serde_json::to_string(&cursor).unwrap();
+		assert_eq!(serialized, r#"{"blockNumber":"0x1b4","logIndex":"0x2"}"#);
+		assert_eq!(serde_json::from_str::<LogCursor>(&serialized).unwrap(), cursor);
+	}
+}
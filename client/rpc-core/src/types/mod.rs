@@ -28,8 +28,11 @@ mod index;
 mod log;
 mod receipt;
 mod sync;
+mod trace;
+mod trace_filter;
 mod transaction;
 mod transaction_request;
+mod txpool;
 mod work;
 
 pub mod pubsub;
@@ -38,20 +41,28 @@ pub use self::account_info::{AccountInfo, ExtAccountInfo, EthAccount, StoragePro
 pub use self::bytes::Bytes;
 pub use self::block::{RichBlock, Block, BlockTransactions, Header, RichHeader, Rich};
 pub use self::block_number::BlockNumber;
-pub use self::call_request::CallRequest;
+pub use self::call_request::{CallRequest, CallStateOverride};
 pub use self::filter::{
 	Filter, FilterChanges, FilterPool, FilterPoolItem, FilterType, VariadicValue, FilterAddress,
 	Topic, FilteredParams,
 };
 pub use self::index::Index;
-pub use self::log::Log;
+pub use self::log::{Log, LogCursor, LogsPage};
 pub use self::receipt::Receipt;
 pub use self::sync::{
-	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
+	SyncStatus, SyncInfo, MappingSyncStatus, MappedBlock, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo, PeerCount,
 };
+pub use self::trace::{TraceParams, Trace, StructLogsTrace, StructLog, CallTrace};
+pub use self::trace_filter::{
+	TraceFilter, LocalizedTrace, Action, CallAction, CreateAction, SuicideAction, TraceResult,
+	CallResult, CreateResult,
+};
 pub use self::transaction::{
 	Transaction, RichRawTransaction, LocalTransactionStatus, PendingTransactions, PendingTransaction,
 };
 pub use self::transaction_request::TransactionRequest;
+pub use self::txpool::{
+	TransactionMap, TxPoolResult, TxPoolContent, TxPoolInspect, TxPoolStatus, Summary,
+};
 pub use self::work::Work;
@@ -18,11 +18,11 @@
 
 //! Pub-Sub types.
 
-use ethereum_types::H256;
+use ethereum_types::{H160, H256};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
-use crate::types::{RichHeader, Filter, Log};
+use crate::types::{RichHeader, Filter, Log, Transaction, SyncInfo};
 
 /// Subscription result.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,6 +33,8 @@ pub enum Result {
 	Log(Box<Log>),
 	/// Transaction hash
 	TransactionHash(H256),
+	/// Transaction
+	Transaction(Box<Transaction>),
 	/// SyncStatus
 	SyncState(PubSubSyncStatus)
 }
@@ -43,6 +45,9 @@ pub enum Result {
 pub struct PubSubSyncStatus {
 	/// is_major_syncing?
 	pub syncing: bool,
+	/// Sync progress, while syncing.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub status: Option<SyncInfo>,
 }
 
 impl Serialize for Result {
@@ -53,6 +58,7 @@ impl Serialize for Result {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::Transaction(ref transaction) => transaction.serialize(serializer),
 			Result::SyncState(ref sync) => sync.serialize(serializer),
 		}
 	}
@@ -65,6 +71,8 @@ impl Serialize for Result {
 pub enum Kind {
 	/// New block headers subscription.
 	NewHeads,
+	/// New finalized block headers subscription.
+	NewFinalizedHeads,
 	/// Logs subscription.
 	Logs,
 	/// New Pending Transactions subscription.
@@ -80,6 +88,36 @@ pub enum Params {
 	None,
 	/// Log parameters.
 	Logs(Filter),
+	/// Pending transactions parameters.
+	PendingTransactions(PendingTransactionsFilter),
+}
+
+/// Pending transactions subscription parameters, either a boolean telling whether to send full
+/// transaction objects or an object restricting the transactions by address.
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Hash, Clone)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingTransactionsFilter {
+	/// Send full transaction objects instead of hashes.
+	#[serde(default)]
+	pub full_transactions: bool,
+	/// Only send transactions from one of these addresses.
+	pub from: Option<Vec<H160>>,
+	/// Only send transactions to one of these addresses.
+	pub to: Option<Vec<H160>>,
+}
+
+impl PendingTransactionsFilter {
+	/// Whether transactions are restricted by address.
+	pub fn has_addresses(&self) -> bool {
+		self.from.is_some() || self.to.is_some()
+	}
+
+	/// Whether a transaction from `from` to `to` passes the filter.
+	pub fn matches(&self, from: &H160, to: Option<&H160>) -> bool {
+		self.from.as_ref().map_or(true, |addresses| addresses.contains(from)) &&
+			self.to.as_ref().map_or(true, |addresses| to.map_or(false, |to| addresses.contains(to)))
+	}
 }
 
 impl Default for Params {
@@ -97,7 +135,45 @@ impl<'a> Deserialize<'a> for Params {
 			return Ok(Params::None);
 		}
 
+		if let Value::Bool(full_transactions) = v {
+			return Ok(Params::PendingTransactions(PendingTransactionsFilter {
+				full_transactions,
+				..Default::default()
+			}));
+		}
+
 		from_value(v.clone()).map(Params::Logs)
+			.or_else(|_| from_value(v).map(Params::PendingTransactions))
 			.map_err(|e| D::Error::custom(format!("Invalid Pub-Sub parameters: {}", e)))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+
+	#[test]
+	fn pending_transactions_params_deserialize() {
+		let full: Params = serde_json::from_str("true").unwrap();
+		assert_eq!(full, Params::PendingTransactions(PendingTransactionsFilter {
+			full_transactions: true,
+			from: None,
+			to: None,
+		}));
+
+		let filtered: Params = serde_json::from_str(
+			r#"{"fullTransactions":true,"to":["0x0000000000000000000000000000000000000001"]}"#
+		).unwrap();
+		let recipient = H160::from_str("0000000000000000000000000000000000000001").unwrap();
+		match filtered {
+			Params::PendingTransactions(filter) => {
+				assert!(filter.full_transactions);
+				assert!(filter.matches(&H160::zero(), Some(&recipient)));
+				assert!(!filter.matches(&recipient, Some(&H160::zero())));
+				assert!(!filter.matches(&recipient, None));
+			},
+			params => panic!("expected pending transactions params, got {:?}", params),
+		}
+	}
+}
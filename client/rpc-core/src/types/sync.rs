@@ -18,11 +18,11 @@
 
 use std::collections::BTreeMap;
 
-use ethereum_types::{U256, H512};
+use ethereum_types::{U256, H256, H512};
 use serde::{Serialize, Serializer};
 
 /// Sync info
-#[derive(Default, Debug, Serialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncInfo {
 	/// Starting block
@@ -35,6 +35,9 @@ pub struct SyncInfo {
 	pub warp_chunks_amount: Option<U256>,
 	/// Warp sync snpashot chunks processed.
 	pub warp_chunks_processed: Option<U256>,
+	/// Blocks not yet mapped by the Frontier mapping sync, counted up to the mapping sync scan
+	/// limit.
+	pub mapping_lag: Option<U256>,
 }
 
 /// Peers info
@@ -149,3 +152,50 @@ pub struct ChainStatus {
 	/// Describes the gap in the blockchain, if there is one: (first, last)
 	pub block_gap: Option<(U256, U256)>,
 }
+
+/// Block known to the Frontier mapping
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct MappedBlock {
+	/// Block number
+	pub number: U256,
+	/// Substrate block hash
+	pub hash: H256,
+}
+
+/// Progress of the Frontier mapping sync, returned by `frontier_syncStatus`
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingSyncStatus {
+	/// Latest canonical block whose mapping is synced
+	pub latest_mapped_block: Option<MappedBlock>,
+	/// Blocks from which the mapping sync walks down the chain
+	pub syncing_tips: Vec<H256>,
+	/// Canonical blocks not yet mapped
+	pub unmapped_blocks: U256,
+	/// Whether `unmapped_blocks` stopped counting at the scan limit
+	pub unmapped_blocks_capped: bool,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn mapping_sync_status_serialization() {
+		let status = MappingSyncStatus {
+			latest_mapped_block: Some(MappedBlock {
+				number: U256::from(16),
+				hash: H256::from_low_u64_be(1),
+			}),
+			syncing_tips: vec![H256::from_low_u64_be(2)],
+			unmapped_blocks: U256::from(3),
+			unmapped_blocks_capped: false,
+		};
+		let serialized = serde_json::to_string(&status).unwrap();
+
+		assert_eq!(
+			serialized,
+			r#"{"latestMappedBlock":{"number":"0x10","hash":"0x0000000000000000000000000000000000000000000000000000000000000001"},"syncingTips":["0x0000000000000000000000000000000000000000000000000000000000000002"],"unmappedBlocks":"0x3","unmappedBlocksCapped":false}"#
+		);
+	}
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use ethereum_types::{H160, H256, U256};
+use crate::types::Bytes;
+
+/// Options of `debug_traceTransaction` and `debug_traceCall`
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceParams {
+	/// Tracer to run, `callTracer` or none for struct logs
+	pub tracer: Option<String>,
+	/// Leave storage out of struct logs
+	pub disable_storage: Option<bool>,
+	/// Leave memory out of struct logs
+	pub disable_memory: Option<bool>,
+	/// Leave the stack out of struct logs
+	pub disable_stack: Option<bool>,
+}
+
+/// Trace of a transaction
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Trace {
+	/// Opcode level logs
+	StructLogs(StructLogsTrace),
+	/// Call frames, as returned by `callTracer`
+	Call(CallTrace),
+}
+
+/// Struct logs of a transaction
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogsTrace {
+	/// Gas used
+	pub gas: U256,
+	/// Whether the transaction failed
+	pub failed: bool,
+	/// Return value
+	pub return_value: Bytes,
+	/// Logs
+	pub struct_logs: Vec<StructLog>,
+}
+
+/// State of the EVM before executing an opcode
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+	/// Program counter
+	pub pc: u64,
+	/// Opcode name
+	pub op: String,
+	/// Gas left
+	pub gas: u64,
+	/// Gas cost of the opcode
+	pub gas_cost: u64,
+	/// Call depth
+	pub depth: u32,
+	/// Stack
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stack: Option<Vec<U256>>,
+	/// Memory, as 32 byte words
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub memory: Option<Vec<String>>,
+	/// Storage of the current contract accessed so far
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub storage: Option<BTreeMap<H256, H256>>,
+}
+
+/// Call frame
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTrace {
+	/// Call type, such as `CALL` or `CREATE2`
+	#[serde(rename = "type")]
+	pub call_type: String,
+	/// Sender
+	pub from: H160,
+	/// Recipient or created contract
+	pub to: H160,
+	/// Value transferred
+	pub value: U256,
+	/// Gas given to the frame
+	pub gas: U256,
+	/// Gas used by the frame
+	pub gas_used: U256,
+	/// Input data or init code
+	pub input: Bytes,
+	/// Output data
+	pub output: Bytes,
+	/// Error, if the frame failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Set to `ewasm` for frames executed by SSVM
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub vm: Option<String>,
+	/// Frames entered by this one
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub calls: Vec<CallTrace>,
+}
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use serde::{Serialize, Deserialize};
+use ethereum_types::{H160, H256, U256};
+use crate::types::{BlockNumber, Bytes};
+
+/// Filter of `trace_filter`
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFilter {
+	/// From block
+	pub from_block: Option<BlockNumber>,
+	/// To block
+	pub to_block: Option<BlockNumber>,
+	/// Senders to match, any if missing
+	pub from_address: Option<Vec<H160>>,
+	/// Recipients to match, any if missing
+	pub to_address: Option<Vec<H160>>,
+	/// Number of matching traces to skip
+	pub after: Option<usize>,
+	/// Maximum number of traces to return
+	pub count: Option<usize>,
+}
+
+/// Trace of a single call, creation or self destruct, located in the chain
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalizedTrace {
+	/// What was executed
+	pub action: Action,
+	/// Outcome, missing if the execution failed
+	pub result: Option<TraceResult>,
+	/// Error, if the execution failed
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<String>,
+	/// Number of direct subtraces
+	pub subtraces: usize,
+	/// Position in the call tree of the transaction
+	pub trace_address: Vec<usize>,
+	/// `call`, `create` or `suicide`
+	#[serde(rename = "type")]
+	pub trace_type: String,
+	/// Block hash
+	pub block_hash: H256,
+	/// Block number
+	pub block_number: u64,
+	/// Transaction hash
+	pub transaction_hash: H256,
+	/// Transaction index
+	pub transaction_position: usize,
+}
+
+/// Action of a trace
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Action {
+	/// Call
+	Call(CallAction),
+	/// Contract creation
+	Create(CreateAction),
+	/// Self destruct
+	Suicide(SuicideAction),
+}
+
+/// Call action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallAction {
+	/// `call`, `callcode`, `delegatecall` or `staticcall`
+	pub call_type: String,
+	/// Sender
+	pub from: H160,
+	/// Recipient
+	pub to: H160,
+	/// Gas given
+	pub gas: U256,
+	/// Input data
+	pub input: Bytes,
+	/// Value transferred
+	pub value: U256,
+}
+
+/// Contract creation action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateAction {
+	/// Creator
+	pub from: H160,
+	/// Gas given
+	pub gas: U256,
+	/// Init code
+	pub init: Bytes,
+	/// Value transferred
+	pub value: U256,
+}
+
+/// Self destruct action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuicideAction {
+	/// Destroyed contract
+	pub address: H160,
+	/// Balance sent to the refund address
+	pub balance: U256,
+	/// Refund address
+	pub refund_address: H160,
+}
+
+/// Outcome of a trace
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+	/// Call outcome
+	Call(CallResult),
+	/// Contract creation outcome
+	Create(CreateResult),
+}
+
+/// Call outcome
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallResult {
+	/// Gas used
+	pub gas_used: U256,
+	/// Output data
+	pub output: Bytes,
+}
+
+/// Contract creation outcome
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateResult {
+	/// Created contract
+	pub address: H160,
+	/// Deployed code
+	pub code: Bytes,
+	/// Gas used
+	pub gas_used: U256,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn trace_filter_deserialization() {
+		let s = r#"{
+			"fromBlock": "0x1",
+			"toBlock": "latest",
+			"toAddress": ["0x0000000000000000000000000000000000000002"],
+			"after": 1,
+			"count": 10
+		}"#;
+		let deserialized: TraceFilter = serde_json::from_str(s).unwrap();
+
+		assert_eq!(deserialized, TraceFilter {
+			from_block: Some(BlockNumber::Num(1)),
+			to_block: Some(BlockNumber::Latest),
+			from_address: None,
+			to_address: Some(vec![H160::from_low_u64_be(2)]),
+			after: Some(1),
+			count: Some(10),
+		});
+	}
+
+	#[test]
+	fn call_trace_serialization() {
+		let trace = LocalizedTrace {
+			action: Action::Call(CallAction {
+				call_type: "call".into(),
+				from: H160::from_low_u64_be(1),
+				to: H160::from_low_u64_be(2),
+				gas: U256::from(21000),
+				input: Bytes(vec![]),
+				value: U256::zero(),
+			}),
+			result: None,
+			error: Some("Reverted".into()),
+			subtraces: 0,
+			trace_address: vec![0],
+			trace_type: "call".into(),
+			block_hash: H256::zero(),
+			block_number: 1,
+			transaction_hash: H256::zero(),
+			transaction_position: 0,
+		};
+		let serialized = serde_json::to_value(&trace).unwrap();
+
+		assert_eq!(serialized["type"], "call");
+		assert_eq!(serialized["action"]["callType"], "call");
+		assert_eq!(serialized["result"], serde_json::Value::Null);
+		assert_eq!(serialized["error"], "Reverted");
+		assert_eq!(serialized["traceAddress"], serde_json::json!([0]));
+	}
+}
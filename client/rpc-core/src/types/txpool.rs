@@ -0,0 +1,97 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2015-2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+use std::collections::BTreeMap;
+use serde::{Serialize, Serializer};
+use ethereum_types::{H160, U256};
+use crate::types::Transaction;
+
+/// Transactions of the pool by sender and nonce, the nonce being a decimal string.
+pub type TransactionMap<T> = BTreeMap<H160, BTreeMap<String, T>>;
+
+/// Pool contents split by whether they can be included in the next block
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TxPoolResult<T> {
+	/// Transactions ready for inclusion
+	pub pending: T,
+	/// Transactions waiting for a nonce gap to be filled
+	pub queued: T,
+}
+
+/// Response of `txpool_content`
+pub type TxPoolContent = TxPoolResult<TransactionMap<Transaction>>;
+/// Response of `txpool_inspect`
+pub type TxPoolInspect = TxPoolResult<TransactionMap<Summary>>;
+/// Response of `txpool_status`
+pub type TxPoolStatus = TxPoolResult<U256>;
+
+/// Textual summary of a pool transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+	/// Recipient, `None` for contract creation
+	pub to: Option<H160>,
+	/// Value transferred
+	pub value: U256,
+	/// Gas limit
+	pub gas: U256,
+	/// Gas price
+	pub gas_price: U256,
+}
+
+impl Serialize for Summary {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		let to = match self.to {
+			Some(to) => format!("{:?}", to),
+			None => "contract creation".to_string(),
+		};
+		serializer.serialize_str(&format!(
+			"{}: {} wei + {} gas × {} wei",
+			to, self.value, self.gas, self.gas_price,
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn summary_serialization() {
+		let mut pending = BTreeMap::new();
+		pending.insert(H160::from_low_u64_be(1), vec![
+			("9".to_string(), Summary {
+				to: Some(H160::from_low_u64_be(2)),
+				value: U256::from(1000),
+				gas: U256::from(21000),
+				gas_price: U256::from(1),
+			}),
+		].into_iter().collect());
+		let inspect = TxPoolInspect {
+			pending,
+			queued: BTreeMap::new(),
+		};
+		let serialized = serde_json::to_string(&inspect).unwrap();
+
+		assert_eq!(
+			serialized,
+			r#"{"pending":{"0x0000000000000000000000000000000000000001":{"9":"0x0000000000000000000000000000000000000002: 1000 wei + 21000 gas × 1 wei"}},"queued":{}}"#
+		);
+	}
+}
@@ -0,0 +1,401 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{marker::PhantomData, sync::Arc};
+use ethereum_types::{H256, U256};
+use jsonrpc_core::Result;
+use rustc_hex::ToHex;
+use sp_runtime::traits::{Block as BlockT, BlakeTwo256};
+use sp_api::{ProvideRuntimeApi, ApiExt, BlockId, Core, HeaderT};
+use sp_blockchain::HeaderBackend;
+use sc_client_api::backend::{StorageProvider, Backend, StateBackend, AuxStore};
+use sc_client_api::BlockBackend;
+use fc_rpc_core::DebugApi as DebugApiT;
+use fc_rpc_core::types::{
+	BlockNumber, Bytes, CallRequest, CallTrace, StructLog, StructLogsTrace, Trace, TraceParams,
+};
+use fp_rpc::{EthereumRuntimeRPCApi, DebugRuntimeApi};
+use fp_vm::tracing::{CallFrame, CallType, FrameVm, StructLogConfig, TracerKind, TransactionTrace};
+use crate::{frontier_backend_client, internal_err};
+use crate::overrides::OverrideHandle;
+
+pub use fc_rpc_core::DebugApiServer;
+
+pub struct DebugApi<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	overrides: Arc<OverrideHandle<B>>,
+	_marker: PhantomData<BE>,
+}
+
+impl<B: BlockT, C, BE> DebugApi<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		overrides: Arc<OverrideHandle<B>>,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			overrides,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C, BE> DebugApi<B, C, BE> where
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B>,
+	C::Api: DebugRuntimeApi<B>,
+{
+	fn ensure_tracing_supported(&self, id: &BlockId<B>) -> Result<()> {
+		let supported = self.client.runtime_api().has_api::<dyn DebugRuntimeApi<B>>(id)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		if !supported {
+			return Err(internal_err("runtime does not support tracing"));
+		}
+		Ok(())
+	}
+}
+
+impl<B, C, BE> DebugApiT for DebugApi<B, C, BE> where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + AuxStore,
+	C: HeaderBackend<B> + BlockBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B> + DebugRuntimeApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+{
+	fn trace_transaction(&self, hash: H256, params: Option<TraceParams>) -> Result<Trace> {
+		let tracer = tracer_kind(params)?;
+
+		let (ethereum_block_hash, index) = match frontier_backend_client::load_transactions::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			hash,
+		)? {
+			Some((hash, index)) => (hash, index as usize),
+			None => return Err(internal_err("transaction not found")),
+		};
+		let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), ethereum_block_hash)? {
+			Some(id) => id,
+			None => return Err(internal_err("block not found")),
+		};
+
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+		let handler = self.overrides.schemas.get(&schema).unwrap_or(&self.overrides.fallback);
+		let transaction = match handler.current_block(&id) {
+			Some(block) if index < block.transactions.len() => block.transactions[index].clone(),
+			_ => return Err(internal_err("transaction not found in block")),
+		};
+
+		let header = self.client.header(id)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("header not found"))?;
+		let extrinsics = self.client.block_body(&id)
+			.map_err(|err| internal_err(format!("fetch block body failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("block body not found"))?;
+
+		// Replay the block on top of its parent state.
+		let parent_id = BlockId::Hash(*header.parent_hash());
+		self.ensure_tracing_supported(&parent_id)?;
+
+		let api = self.client.runtime_api();
+		api.initialize_block(&parent_id, &header)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		let trace = api.trace_transaction(&parent_id, extrinsics, transaction, tracer)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("trace failed: {:?}", err)))?;
+
+		Ok(trace_build(trace))
+	}
+
+	fn trace_call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		params: Option<TraceParams>,
+	) -> Result<Trace> {
+		let tracer = tracer_kind(params)?;
+
+		let id = match frontier_backend_client::native_block_id::<B, C>(self.client.as_ref(), self.backend.as_ref(), number.clone())? {
+			Some(id) => id,
+			// There is no separate pending state, calls against it run on top of the best block.
+			None if matches!(number, Some(BlockNumber::Pending)) => BlockId::Hash(self.client.info().best_hash),
+			None => return Err(internal_err("header not found")),
+		};
+		self.ensure_tracing_supported(&id)?;
+
+		let CallRequest {
+			from,
+			to,
+			gas_price,
+			gas,
+			value,
+			data,
+			nonce
+		} = request;
+
+		// use given gas limit or query current block's limit
+		let gas_limit = match gas {
+			Some(amount) => amount,
+			None => {
+				let block = self.client.runtime_api().current_block(&id)
+					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+				if let Some(block) = block {
+					block.header.gas_limit
+				} else {
+					return Err(internal_err("block unavailable, cannot query gas limit"));
+				}
+			},
+		};
+
+		let trace = self.client.runtime_api()
+			.trace_call(
+				&id,
+				from.unwrap_or_default(),
+				to,
+				data.map(|d| d.0).unwrap_or_default(),
+				value.unwrap_or_default(),
+				gas_limit,
+				gas_price,
+				nonce,
+				tracer,
+			)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("trace failed: {:?}", err)))?;
+
+		Ok(trace_build(trace))
+	}
+}
+
+fn tracer_kind(params: Option<TraceParams>) -> Result<TracerKind> {
+	let params = params.unwrap_or_default();
+	match params.tracer.as_deref() {
+		None => Ok(TracerKind::StructLog(StructLogConfig {
+			disable_storage: params.disable_storage.unwrap_or(false),
+			disable_memory: params.disable_memory.unwrap_or(false),
+			disable_stack: params.disable_stack.unwrap_or(false),
+		})),
+		Some("callTracer") => Ok(TracerKind::Call),
+		Some(tracer) => Err(internal_err(format!("unsupported tracer: {}", tracer))),
+	}
+}
+
+fn trace_build(trace: TransactionTrace) -> Trace {
+	match trace {
+		TransactionTrace::StructLogs { gas, failed, return_value, struct_logs } => {
+			Trace::StructLogs(StructLogsTrace {
+				gas: U256::from(gas),
+				failed,
+				return_value: Bytes(return_value),
+				struct_logs: struct_logs.into_iter().map(|log| StructLog {
+					pc: log.pc,
+					op: opcode_name(log.op).to_string(),
+					gas: log.gas,
+					gas_cost: log.gas_cost,
+					depth: log.depth,
+					stack: log.stack.map(|stack| {
+						stack.iter().map(|item| U256::from_big_endian(item.as_bytes())).collect()
+					}),
+					memory: log.memory.map(|memory| {
+						memory.chunks(32).map(|word| word.to_hex::<String>()).collect()
+					}),
+					storage: log.storage.map(|storage| storage.into_iter().collect()),
+				}).collect(),
+			})
+		},
+		TransactionTrace::CallTrace(frame) => Trace::Call(call_trace_build(frame)),
+	}
+}
+
+fn call_trace_build(frame: CallFrame) -> CallTrace {
+	CallTrace {
+		call_type: match frame.call_type {
+			CallType::Call => "CALL",
+			CallType::CallCode => "CALLCODE",
+			CallType::DelegateCall => "DELEGATECALL",
+			CallType::StaticCall => "STATICCALL",
+			CallType::Create => "CREATE",
+			CallType::Create2 => "CREATE2",
+			CallType::SelfDestruct => "SELFDESTRUCT",
+		}.to_string(),
+		from: frame.from,
+		to: frame.to,
+		value: frame.value,
+		gas: U256::from(frame.gas),
+		gas_used: U256::from(frame.gas_used),
+		input: Bytes(frame.input),
+		output: Bytes(frame.output),
+		error: frame.error.map(|error| String::from_utf8_lossy(&error).into_owned()),
+		vm: match frame.vm {
+			FrameVm::Evm => None,
+			FrameVm::Ewasm => Some("ewasm".to_string()),
+		},
+		calls: frame.calls.into_iter().map(call_trace_build).collect(),
+	}
+}
+
+/// Mnemonic of an EVM opcode.
+fn opcode_name(opcode: u8) -> &'static str {
+	match opcode {
+		0x00 => "STOP",
+		0x01 => "ADD",
+		0x02 => "MUL",
+		0x03 => "SUB",
+		0x04 => "DIV",
+		0x05 => "SDIV",
+		0x06 => "MOD",
+		0x07 => "SMOD",
+		0x08 => "ADDMOD",
+		0x09 => "MULMOD",
+		0x0a => "EXP",
+		0x0b => "SIGNEXTEND",
+		0x10 => "LT",
+		0x11 => "GT",
+		0x12 => "SLT",
+		0x13 => "SGT",
+		0x14 => "EQ",
+		0x15 => "ISZERO",
+		0x16 => "AND",
+		0x17 => "OR",
+		0x18 => "XOR",
+		0x19 => "NOT",
+		0x1a => "BYTE",
+		0x1b => "SHL",
+		0x1c => "SHR",
+		0x1d => "SAR",
+		0x20 => "SHA3",
+		0x30 => "ADDRESS",
+		0x31 => "BALANCE",
+		0x32 => "ORIGIN",
+		0x33 => "CALLER",
+		0x34 => "CALLVALUE",
+		0x35 => "CALLDATALOAD",
+		0x36 => "CALLDATASIZE",
+		0x37 => "CALLDATACOPY",
+		0x38 => "CODESIZE",
+		0x39 => "CODECOPY",
+		0x3a => "GASPRICE",
+		0x3b => "EXTCODESIZE",
+		0x3c => "EXTCODECOPY",
+		0x3d => "RETURNDATASIZE",
+		0x3e => "RETURNDATACOPY",
+		0x3f => "EXTCODEHASH",
+		0x40 => "BLOCKHASH",
+		0x41 => "COINBASE",
+		0x42 => "TIMESTAMP",
+		0x43 => "NUMBER",
+		0x44 => "DIFFICULTY",
+		0x45 => "GASLIMIT",
+		0x46 => "CHAINID",
+		0x47 => "SELFBALANCE",
+		0x50 => "POP",
+		0x51 => "MLOAD",
+		0x52 => "MSTORE",
+		0x53 => "MSTORE8",
+		0x54 => "SLOAD",
+		0x55 => "SSTORE",
+		0x56 => "JUMP",
+		0x57 => "JUMPI",
+		0x58 => "PC",
+		0x59 => "MSIZE",
+		0x5a => "GAS",
+		0x5b => "JUMPDEST",
+		0x60 => "PUSH1",
+		0x61 => "PUSH2",
+		0x62 => "PUSH3",
+		0x63 => "PUSH4",
+		0x64 => "PUSH5",
+		0x65 => "PUSH6",
+		0x66 => "PUSH7",
+		0x67 => "PUSH8",
+		0x68 => "PUSH9",
+		0x69 => "PUSH10",
+		0x6a => "PUSH11",
+		0x6b => "PUSH12",
+		0x6c => "PUSH13",
+		0x6d => "PUSH14",
+		0x6e => "PUSH15",
+		0x6f => "PUSH16",
+		0x70 => "PUSH17",
+		0x71 => "PUSH18",
+		0x72 => "PUSH19",
+		0x73 => "PUSH20",
+		0x74 => "PUSH21",
+		0x75 => "PUSH22",
+		0x76 => "PUSH23",
+		0x77 => "PUSH24",
+		0x78 => "PUSH25",
+		0x79 => "PUSH26",
+		0x7a => "PUSH27",
+		0x7b => "PUSH28",
+		0x7c => "PUSH29",
+		0x7d => "PUSH30",
+		0x7e => "PUSH31",
+		0x7f => "PUSH32",
+		0x80 => "DUP1",
+		0x81 => "DUP2",
+		0x82 => "DUP3",
+		0x83 => "DUP4",
+		0x84 => "DUP5",
+		0x85 => "DUP6",
+		0x86 => "DUP7",
+		0x87 => "DUP8",
+		0x88 => "DUP9",
+		0x89 => "DUP10",
+		0x8a => "DUP11",
+		0x8b => "DUP12",
+		0x8c => "DUP13",
+		0x8d => "DUP14",
+		0x8e => "DUP15",
+		0x8f => "DUP16",
+		0x90 => "SWAP1",
+		0x91 => "SWAP2",
+		0x92 => "SWAP3",
+		0x93 => "SWAP4",
+		0x94 => "SWAP5",
+		0x95 => "SWAP6",
+		0x96 => "SWAP7",
+		0x97 => "SWAP8",
+		0x98 => "SWAP9",
+		0x99 => "SWAP10",
+		0x9a => "SWAP11",
+		0x9b => "SWAP12",
+		0x9c => "SWAP13",
+		0x9d => "SWAP14",
+		0x9e => "SWAP15",
+		0x9f => "SWAP16",
+		0xa0 => "LOG0",
+		0xa1 => "LOG1",
+		0xa2 => "LOG2",
+		0xa3 => "LOG3",
+		0xa4 => "LOG4",
+		0xf0 => "CREATE",
+		0xf1 => "CALL",
+		0xf2 => "CALLCODE",
+		0xf3 => "RETURN",
+		0xf4 => "DELEGATECALL",
+		0xf5 => "CREATE2",
+		0xfa => "STATICCALL",
+		0xfd => "REVERT",
+		0xfe => "INVALID",
+		0xff => "SELFDESTRUCT",
+		_ => "UNKNOWN",
+	}
+}
@@ -16,48 +16,61 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 use std::{marker::PhantomData, time, sync::{Mutex, Arc}};
-use std::collections::{HashMap, BTreeMap};
+use std::collections::{HashMap, BTreeMap, BTreeSet};
 use ethereum::{
 	Block as EthereumBlock, Transaction as EthereumTransaction
 };
 use ethereum_types::{H160, H256, H64, U256, U64, H512};
-use jsonrpc_core::{BoxFuture, Result, ErrorCode, futures::future::{self, Future}};
+use jsonrpc_core::{BoxFuture, Result, futures::future::{self, Future}};
 use futures::{StreamExt, future::TryFutureExt};
 use sp_runtime::{
 	traits::{Block as BlockT, UniqueSaturatedInto, Zero, One, Saturating, BlakeTwo256, NumberFor},
 	transaction_validity::TransactionSource,
 };
-use sp_api::{ProvideRuntimeApi, BlockId, Core, HeaderT};
+use sp_api::{ProvideRuntimeApi, ApiExt, BlockId, Core, HeaderT};
 use sp_transaction_pool::{TransactionPool, InPoolTransaction};
-use sc_client_api::{client::BlockchainEvents, backend::{StorageProvider, Backend, StateBackend, AuxStore}};
+use sc_client_api::{client::BlockchainEvents, backend::{StorageProvider, Backend, StateBackend, AuxStore}, ProofProvider};
 use sha3::{Keccak256, Digest};
 use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sc_network::{NetworkService, ExHashT};
+use sc_rpc::system::Request as SystemRequest;
+use sp_utils::mpsc::TracingUnboundedSender;
 use fc_rpc_core::{
 	EthApi as EthApiT, NetApi as NetApiT, Web3Api as Web3ApiT, EthFilterApi as EthFilterApiT
 };
 use fc_rpc_core::types::{
-	BlockNumber, Bytes, CallRequest, Filter, FilteredParams, FilterChanges, FilterPool, FilterPoolItem,
-	FilterType, Index, Log, Receipt, RichBlock, SyncStatus, SyncInfo, Transaction, Work, Rich, Block,
+	BlockNumber, Bytes, CallRequest, CallStateOverride, Filter, FilteredParams, FilterChanges, FilterPool, FilterPoolItem,
+	FilterType, VariadicValue, LogCursor, LogsPage, Index, Log, Receipt, RichBlock, SyncStatus, SyncInfo, Transaction, Work, Rich, Block,
+	EthAccount, StorageProof,
 	BlockTransactions, TransactionRequest, PendingTransactions, PendingTransaction, PeerCount,
 };
-use fp_rpc::{EthereumRuntimeRPCApi, ConvertTransaction, TransactionStatus};
-use crate::{frontier_backend_client, internal_err, error_on_execution_failure, EthSigner, public_key};
+use fp_rpc::{EthereumRuntimeRPCApi, ConvertTransaction, TransactionStatus, TxPoolRuntimeApi, EIP658Receipt};
+use crate::{
+	frontier_backend_client, internal_err, error_on_execution_failure, is_success, is_out_of_gas,
+	network_sync_info, EthSigner, public_key,
+};
+use pallet_vm::ExtendExitReason;
+use fc_mapping_sync::SharedMappingLag;
 
 pub use fc_rpc_core::{EthApiServer, NetApiServer, Web3ApiServer, EthFilterApiServer};
 use codec::{self, Encode};
 use crate::overrides::OverrideHandle;
 
+/// Blocks the mapping sync may be behind before `eth_syncing` reports the node as syncing.
+const MAX_MAPPING_LAG: u32 = 5;
+
 pub struct EthApi<B: BlockT, C, P, CT, BE, H: ExHashT> {
 	pool: Arc<P>,
 	client: Arc<C>,
 	convert_transaction: CT,
 	network: Arc<NetworkService<B, H>>,
+	system_rpc_tx: TracingUnboundedSender<SystemRequest<B>>,
 	is_authority: bool,
 	signers: Vec<Box<dyn EthSigner>>,
 	overrides: Arc<OverrideHandle<B>>,
 	pending_transactions: PendingTransactions,
 	backend: Arc<fc_db::Backend<B>>,
+	mapping_lag: SharedMappingLag<B>,
 	max_past_logs: u32,
 	_marker: PhantomData<(B, BE)>,
 }
@@ -73,10 +86,12 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT> EthApi<B, C, P, CT, BE, H> where
 		pool: Arc<P>,
 		convert_transaction: CT,
 		network: Arc<NetworkService<B, H>>,
+		system_rpc_tx: TracingUnboundedSender<SystemRequest<B>>,
 		pending_transactions: PendingTransactions,
 		signers: Vec<Box<dyn EthSigner>>,
 		overrides: Arc<OverrideHandle<B>>,
 		backend: Arc<fc_db::Backend<B>>,
+		mapping_lag: SharedMappingLag<B>,
 		is_authority: bool,
 		max_past_logs: u32,
 	) -> Self {
@@ -85,11 +100,13 @@ impl<B: BlockT, C, P, CT, BE, H: ExHashT> EthApi<B, C, P, CT, BE, H> where
 			pool,
 			convert_transaction,
 			network,
+			system_rpc_tx,
 			is_authority,
 			signers,
 			overrides,
 			pending_transactions,
 			backend,
+			mapping_lag,
 			max_past_logs,
 			_marker: PhantomData,
 		}
@@ -156,7 +173,66 @@ fn rich_block_build(
 	}
 }
 
-fn transaction_build(
+/// Convert the `eth_call` state overrides into their runtime representation.
+fn convert_state_overrides(
+	state_overrides: BTreeMap<H160, CallStateOverride>,
+) -> Result<Vec<(H160, fp_vm::StateOverride)>> {
+	state_overrides.into_iter().map(|(address, state_override)| {
+		if state_override.state.is_some() && state_override.state_diff.is_some() {
+			return Err(internal_err(format!(
+				"state and stateDiff can't be used together for account {:?}", address
+			)));
+		}
+
+		Ok((address, fp_vm::StateOverride {
+			balance: state_override.balance,
+			nonce: state_override.nonce,
+			code: state_override.code.map(|code| code.0),
+			state: state_override.state.map(|state| state.into_iter().collect()),
+			state_diff: state_override.state_diff.map(|state_diff| state_diff.into_iter().collect()),
+		}))
+	}).collect()
+}
+
+/// Execute a call through the runtime api. Runtimes that predate version 2 of
+/// `EthereumRuntimeRPCApi` can't apply state overrides, so passing any is an error.
+fn runtime_call<B, C>(
+	client: &C,
+	id: &BlockId<B>,
+	from: H160,
+	to: H160,
+	data: Vec<u8>,
+	value: U256,
+	gas_limit: U256,
+	gas_price: Option<U256>,
+	nonce: Option<U256>,
+	estimate: bool,
+	state_overrides: Vec<(H160, fp_vm::StateOverride)>,
+) -> Result<fp_vm::CallInfo> where
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: ProvideRuntimeApi<B>,
+	C::Api: EthereumRuntimeRPCApi<B>,
+{
+	let api = client.runtime_api();
+	let has_state_overrides = api.has_api_with::<dyn EthereumRuntimeRPCApi<B>, _>(id, |version| version >= 2)
+		.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+
+	let info = if has_state_overrides {
+		api.call(id, from, to, data, value, gas_limit, gas_price, nonce, estimate, state_overrides)
+	} else {
+		if !state_overrides.is_empty() {
+			return Err(internal_err("state overrides are not supported by the runtime"));
+		}
+		#[allow(deprecated)]
+		let info = api.call_before_version_2(id, from, to, data, value, gas_limit, gas_price, nonce, estimate);
+		info
+	};
+
+	info.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+		.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))
+}
+
+pub(crate) fn transaction_build(
 	transaction: EthereumTransaction,
 	block: Option<EthereumBlock>,
 	status: Option<TransactionStatus>
@@ -213,8 +289,161 @@ fn transaction_build(
 	}
 }
 
+/// Build the receipts of the first `count` transactions of a block.
+fn receipts_build(
+	block: &EthereumBlock,
+	statuses: &[TransactionStatus],
+	receipts: &[EIP658Receipt],
+	count: usize,
+) -> Vec<Receipt> {
+	let block_hash = H256::from_slice(
+		Keccak256::digest(&rlp::encode(&block.header)).as_slice()
+	);
+
+	let mut previous_cumulative_gas = U256::zero();
+	let mut block_log_index: u32 = 0;
+	statuses.iter().zip(receipts.iter()).take(count).map(|(status, receipt)| {
+		let gas_used = receipt.cumulative_gas_used.saturating_sub(previous_cumulative_gas);
+		previous_cumulative_gas = receipt.cumulative_gas_used;
+		let effective_gas_price = block.transactions.get(status.transaction_index as usize)
+			.map(|transaction| transaction.gas_price)
+			.unwrap_or_default();
+		let logs = receipt.logs.iter().enumerate().map(|(i, log)| {
+			let log = Log {
+				address: log.address,
+				topics: log.topics.clone(),
+				data: Bytes(log.data.clone()),
+				block_hash: Some(block_hash),
+				block_number: Some(block.header.number),
+				transaction_hash: Some(status.transaction_hash),
+				transaction_index: Some(status.transaction_index.into()),
+				log_index: Some(U256::from(block_log_index)),
+				transaction_log_index: Some(U256::from(i)),
+				removed: false,
+			};
+			block_log_index += 1;
+			log
+		}).collect();
+
+		Receipt {
+			transaction_hash: Some(status.transaction_hash),
+			transaction_index: Some(status.transaction_index.into()),
+			block_hash: Some(block_hash),
+			from: Some(status.from),
+			to: status.to,
+			block_number: Some(block.header.number),
+			cumulative_gas_used: receipt.cumulative_gas_used,
+			gas_used: Some(gas_used),
+			contract_address: status.contract_address,
+			logs,
+			status_code: Some(U64::from(receipt.status_code)),
+			logs_bloom: receipt.logs_bloom,
+			state_root: None,
+			effective_gas_price,
+			// Only legacy transactions are supported.
+			transaction_type: U64::zero(),
+		}
+	}).collect()
+}
+
+/// Addresses and, by position, topics that a log must have to match the filter, in the form
+/// expected by the log index. `None` when the filter matches every log.
+fn log_index_constraints(filter: &Filter) -> Option<(Vec<H160>, Vec<Vec<H256>>)> {
+	let addresses = match &filter.address {
+		Some(VariadicValue::Single(address)) => vec![*address],
+		Some(VariadicValue::Multiple(addresses)) => addresses.clone(),
+		_ => Vec::new(),
+	};
+
+	let topics: Vec<Vec<H256>> = match &filter.topics {
+		Some(VariadicValue::Single(Some(VariadicValue::Single(topic)))) => {
+			vec![topic.into_iter().cloned().collect()]
+		},
+		Some(VariadicValue::Single(Some(VariadicValue::Multiple(topics)))) => {
+			topics.iter().map(|topic| topic.into_iter().cloned().collect()).collect()
+		},
+		Some(VariadicValue::Multiple(topics)) => {
+			topics.iter().map(|topic| match topic {
+				Some(VariadicValue::Single(topic)) => topic.into_iter().cloned().collect(),
+				// A wildcard among the alternatives matches any topic.
+				Some(VariadicValue::Multiple(alternatives)) if alternatives.iter().all(Option::is_some) => {
+					alternatives.iter().flatten().cloned().collect()
+				},
+				_ => Vec::new(),
+			}).collect()
+		},
+		_ => Vec::new(),
+	};
+
+	if addresses.is_empty() && topics.iter().all(Vec::is_empty) {
+		None
+	} else {
+		Some((addresses, topics))
+	}
+}
+
+/// Canonical blocks between `from` and `to` that hold logs possibly matching the constraints,
+/// in ascending order, read from the log index. `None` when the range is not indexed.
+fn indexed_log_blocks<B: BlockT, C>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	addresses: &[H160],
+	topics: &[Vec<H256>],
+	from: NumberFor<B>,
+	to: NumberFor<B>,
+) -> Result<Option<Vec<(u64, B::Hash)>>> where
+	C: HeaderBackend<B>,
+{
+	let canonical_hash = |number: u64| -> Result<Option<B::Hash>> {
+		client.hash(number.unique_saturated_into())
+			.map_err(|err| internal_err(format!("{:?}", err)))
+	};
+	let from = UniqueSaturatedInto::<u64>::unique_saturated_into(from);
+	let to = UniqueSaturatedInto::<u64>::unique_saturated_into(to);
+
+	// Coverage is kept per bucket of blocks, and counts the blocks of every fork.
+	if !backend.logs().is_range_indexed(from, to).map_err(internal_err)? {
+		return Ok(None);
+	}
+	// The mapping sync indexes each fork from its tip down. The canonical blocks of a covered
+	// range are indexed if the top one is, and no canonical tip at or above `from` is still
+	// being walked down.
+	match canonical_hash(to)? {
+		Some(hash) if backend.logs().is_indexed(&hash).map_err(internal_err)? => (),
+		_ => return Ok(None),
+	}
+	for tip in backend.meta().current_syncing_tips().map_err(internal_err)? {
+		if backend.mapping().is_synced(&tip).map_err(internal_err)? {
+			continue;
+		}
+		let number = client.number(tip).map_err(|err| internal_err(format!("{:?}", err)))?;
+		if let Some(number) = number {
+			let number = UniqueSaturatedInto::<u64>::unique_saturated_into(number);
+			if number >= from && canonical_hash(number)? == Some(tip) {
+				return Ok(None);
+			}
+		}
+	}
+
+	let entries = backend.logs().query(addresses, topics, from, to).map_err(internal_err)?;
+
+	let candidates: BTreeSet<(u64, B::Hash)> = entries.into_iter()
+		.map(|entry| (entry.block_number, entry.block_hash))
+		.collect();
+
+	let mut blocks = Vec::new();
+	for (number, hash) in candidates {
+		// Blocks of retracted forks are indexed as well.
+		if canonical_hash(number)? == Some(hash) {
+			blocks.push((number, hash));
+		}
+	}
+	Ok(Some(blocks))
+}
+
 fn filter_range_logs<B: BlockT, C, BE>(
 	client: &C,
+	backend: &fc_db::Backend<B>,
 	overrides: &OverrideHandle<B>,
 	ret: &mut Vec<Log>,
 	max_past_logs: u32,
@@ -234,6 +463,34 @@ fn filter_range_logs<B: BlockT, C, BE>(
 	let max_duration = time::Duration::from_secs(10);
 	let begin_request = time::Instant::now();
 
+	if let Some((addresses, topics)) = log_index_constraints(filter) {
+		if let Some(blocks) = indexed_log_blocks(client, backend, &addresses, &topics, from, to)? {
+			for (_, hash) in blocks.into_iter().rev() {
+				let id = BlockId::Hash(hash);
+
+				let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+				let handler = overrides.schemas.get(&schema).unwrap_or(&overrides.fallback);
+
+				let block = handler.current_block(&id);
+				let statuses = handler.current_transaction_statuses(&id);
+				if let (Some(block), Some(statuses)) = (block, statuses) {
+					filter_block_logs(ret, filter, block, statuses);
+				}
+				if ret.len() as u32 > max_past_logs {
+					return Err(internal_err(
+						format!("query returned more than {} results", max_past_logs)
+					));
+				}
+				if begin_request.elapsed() > max_duration {
+					return Err(internal_err(
+						format!("query timeout of {} seconds exceeded", max_duration.as_secs())
+					));
+				}
+			}
+			return Ok(());
+		}
+	}
+
 	let mut current_number = to;
 
 	// Pre-calculate BloomInput for reuse.
@@ -281,6 +538,168 @@ fn filter_range_logs<B: BlockT, C, BE>(
 	Ok(())
 }
 
+/// Collect, in ascending order, at most `page_size` logs matching the filter between blocks `from`
+/// and `to`, skipping the logs of block `from` before `first_log_index`. The page ends early when
+/// the query runs for too long, the returned cursor tells where the next page starts.
+fn filter_logs_page<B: BlockT, C, BE>(
+	client: &C,
+	backend: &fc_db::Backend<B>,
+	overrides: &OverrideHandle<B>,
+	filter: &Filter,
+	from: u64,
+	to: u64,
+	first_log_index: u32,
+	page_size: usize,
+) -> Result<LogsPage> where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+{
+	// Max duration of a page of 5 seconds.
+	let max_duration = time::Duration::from_secs(5);
+	let begin_request = time::Instant::now();
+
+	let indexed = match log_index_constraints(filter) {
+		Some((addresses, topics)) => indexed_log_blocks(
+			client,
+			backend,
+			&addresses,
+			&topics,
+			from.unique_saturated_into(),
+			to.unique_saturated_into(),
+		)?,
+		None => None,
+	};
+	let blocks: Box<dyn Iterator<Item = (u64, BlockId<B>)>> = match indexed {
+		Some(blocks) => Box::new(blocks.into_iter().map(|(number, hash)| (number, BlockId::Hash(hash)))),
+		None => Box::new((from..=to).map(|number| (number, BlockId::Number(number.unique_saturated_into())))),
+	};
+
+	let topics_input = if let Some(_) = &filter.topics {
+		let filtered_params = FilteredParams::new(Some(filter.clone()));
+		Some(filtered_params.flat_topics)
+	} else {
+		None
+	};
+	let bloom_filter = FilteredParams::bloom_filter(&filter.address, &topics_input);
+
+	let block_logs = |id: BlockId<B>| {
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+		let handler = overrides.schemas.get(&schema).unwrap_or(&overrides.fallback);
+
+		let mut block_logs = Vec::new();
+		if let Some(block) = handler.current_block(&id) {
+			if FilteredParams::in_bloom(block.header.logs_bloom, &bloom_filter) {
+				if let Some(statuses) = handler.current_transaction_statuses(&id) {
+					filter_block_logs(&mut block_logs, filter, block, statuses);
+				}
+			}
+		}
+		block_logs
+	};
+
+	Ok(collect_logs_page(
+		blocks,
+		block_logs,
+		from,
+		first_log_index,
+		page_size,
+		|| begin_request.elapsed() > max_duration,
+	))
+}
+
+/// Collect the logs of `blocks`, in ascending order, into a page of at most `page_size` logs. The
+/// logs of block `from` before `first_log_index` were served by the previous page. Once `expired`,
+/// the page ends at the next block.
+fn collect_logs_page<T>(
+	blocks: impl Iterator<Item = (u64, T)>,
+	mut block_logs: impl FnMut(T) -> Vec<Log>,
+	from: u64,
+	first_log_index: u32,
+	page_size: usize,
+	mut expired: impl FnMut() -> bool,
+) -> LogsPage {
+	let mut logs = Vec::new();
+	for (number, id) in blocks {
+		if number != from && expired() {
+			return LogsPage {
+				logs,
+				cursor: Some(LogCursor { block_number: U256::from(number), log_index: U256::zero() }),
+			};
+		}
+
+		// Only the block the cursor points at was partially served.
+		let skip = if number == from { first_log_index } else { 0 };
+		for log in block_logs(id) {
+			let log_index = log.log_index.unwrap_or_default();
+			if log_index < U256::from(skip) {
+				continue;
+			}
+			if logs.len() == page_size {
+				return LogsPage {
+					logs,
+					cursor: Some(LogCursor { block_number: U256::from(number), log_index }),
+				};
+			}
+			logs.push(log);
+		}
+	}
+
+	LogsPage { logs, cursor: None }
+}
+
+/// Add the logs matching the filter of the blocks numbered between `first` and `last` that were
+/// retracted when the best block moved from `from` to `to`, marked as removed. Returns the number
+/// of the last block common to both chains.
+fn retracted_logs<B: BlockT, C, BE>(
+	client: &C,
+	overrides: &OverrideHandle<B>,
+	ret: &mut Vec<Log>,
+	filter: &Filter,
+	from: B::Hash,
+	to: B::Hash,
+	first: NumberFor<B>,
+	last: NumberFor<B>,
+) -> Result<NumberFor<B>> where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE>,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
+	C::Api: EthereumRuntimeRPCApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	C: Send + Sync + 'static,
+{
+	let tree_route = sp_blockchain::tree_route(client, from, to)
+		.map_err(|err| internal_err(format!("{:?}", err)))?;
+
+	for retracted in tree_route.retracted() {
+		if retracted.number < first || retracted.number > last {
+			continue;
+		}
+		let id = BlockId::Hash(retracted.hash);
+
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(client, id);
+		let handler = overrides.schemas.get(&schema).unwrap_or(&overrides.fallback);
+
+		let block = handler.current_block(&id);
+		let statuses = handler.current_transaction_statuses(&id);
+		if let (Some(block), Some(statuses)) = (block, statuses) {
+			let mut logs = Vec::new();
+			filter_block_logs(&mut logs, filter, block, statuses);
+			ret.extend(logs.into_iter().map(|mut log| {
+				log.removed = true;
+				log
+			}));
+		}
+	}
+
+	Ok(tree_route.common_block().number)
+}
+
 fn filter_block_logs<'a>(
 	ret: &'a mut Vec<Log>,
 	filter: &'a Filter,
@@ -346,7 +765,7 @@ fn filter_block_logs<'a>(
 }
 
 impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
-	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + AuxStore,
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + ProofProvider<B> + AuxStore,
 	C: HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
 	C::Api: EthereumRuntimeRPCApi<B>,
 	BE: Backend<B> + 'static,
@@ -360,24 +779,43 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		Ok(1)
 	}
 
-	fn syncing(&self) -> Result<SyncStatus> {
-		if self.network.is_major_syncing() {
-			let block_number = U256::from(
-				UniqueSaturatedInto::<u128>::unique_saturated_into(self.client.info().best_number.clone())
-			);
+	fn syncing(&self) -> BoxFuture<SyncStatus> {
+		let client = self.client.clone();
+		let backend = self.backend.clone();
+		let shared_mapping_lag = self.mapping_lag.clone();
+		let network = self.network.clone();
+		let system_rpc_tx = self.system_rpc_tx.clone();
+		Box::new(Box::pin(async move {
+			let mapping_lag = match fc_mapping_sync::cached_mapping_lag(
+				client.as_ref(),
+				backend.as_ref(),
+				&shared_mapping_lag,
+			) {
+				Ok(lag) => lag.unmapped_blocks,
+				Err(err) => return Err(internal_err(err)),
+			};
+
+			// A node whose mapping is behind serves incomplete data, it is reported as syncing.
+			if !network.is_major_syncing() && mapping_lag <= MAX_MAPPING_LAG {
+				return Ok(SyncStatus::None);
+			}
+
+			let sync_info = network_sync_info(system_rpc_tx).await.unwrap_or_else(|| {
+				let block_number = U256::from(
+					UniqueSaturatedInto::<u128>::unique_saturated_into(client.info().best_number)
+				);
+				SyncInfo {
+					starting_block: U256::zero(),
+					current_block: block_number,
+					highest_block: block_number,
+					..Default::default()
+				}
+			});
 			Ok(SyncStatus::Info(SyncInfo {
-				starting_block: U256::zero(),
-				current_block: block_number,
-				// TODO `highest_block` is not correct, should load `best_seen_block` from NetworkWorker,
-				// but afaik that is not currently possible in Substrate:
-				// https://github.com/paritytech/substrate/issues/7311
-				highest_block: block_number,
-				warp_chunks_amount: None,
-				warp_chunks_processed: None,
+				mapping_lag: Some(U256::from(mapping_lag)),
+				..sync_info
 			}))
-		} else {
-			Ok(SyncStatus::None)
-		}
+		}).compat())
 	}
 
 	fn hashrate(&self) -> Result<U256> {
@@ -459,6 +897,63 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		Ok(H256::default())
 	}
 
+	fn proof(&self, address: H160, storage_keys: Vec<U256>, number: Option<BlockNumber>) -> Result<EthAccount> {
+		let id = match frontier_backend_client::native_block_id::<B, C>(self.client.as_ref(), self.backend.as_ref(), number)? {
+			Some(id) => id,
+			None => return Err(internal_err("header not found")),
+		};
+		let header = self.client.header(id)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("header not found"))?;
+
+		let api = self.client.runtime_api();
+		let supported = api.has_api_with::<dyn EthereumRuntimeRPCApi<B>, _>(&id, |version| version >= 3)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		if !supported {
+			return Err(internal_err("runtime does not support storage proofs"));
+		}
+
+		let indices: Vec<H256> = storage_keys.iter().map(|key| {
+			let mut tmp = [0u8; 32];
+			key.to_big_endian(&mut tmp);
+			H256::from(tmp)
+		}).collect();
+		let keys = api.account_proof_keys(&id, address, indices.clone())
+			.map_err(|err| internal_err(format!("fetch runtime account proof keys failed: {:?}", err)))?;
+		let account = api.account_basic(&id, address)
+			.map_err(|err| internal_err(format!("fetch runtime account basic failed: {:?}", err)))?;
+		let code = api.account_code_at(&id, address)
+			.map_err(|err| internal_err(format!("fetch runtime account code failed: {:?}", err)))?;
+
+		let read_proof = |keys: &[&[u8]]| {
+			frontier_backend_client::read_proof::<B, C>(self.client.as_ref(), &id, keys)
+		};
+
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+		let handler = self.overrides.schemas.get(&schema).unwrap_or(&self.overrides.fallback);
+		let storage_proof = storage_keys.into_iter().zip(keys.storage.iter())
+			.map(|(index, key)| {
+				let value = handler.storage_at(&id, address, index).unwrap_or_default();
+				Ok(StorageProof {
+					key: index,
+					value: U256::from_big_endian(value.as_bytes()),
+					proof: read_proof(&[key.as_slice()])?,
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		Ok(EthAccount {
+			address,
+			balance: account.balance,
+			nonce: account.nonce,
+			code_hash: H256::from_slice(Keccak256::digest(&code).as_slice()),
+			// Substrate has a single trie, every proof is against the state root.
+			storage_hash: *header.state_root(),
+			account_proof: read_proof(&[keys.account.as_slice(), keys.code.as_slice()])?,
+			storage_proof,
+		})
+	}
+
 	fn block_by_hash(&self, hash: H256, full: bool) -> Result<Option<RichBlock>> {
 		let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), hash)
 			.map_err(|err| internal_err(format!("{:?}", err)))?
@@ -752,8 +1247,19 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		)
 	}
 
-	fn call(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<Bytes> {
-		let hash = self.client.info().best_hash;
+	fn call(
+		&self,
+		request: CallRequest,
+		number: Option<BlockNumber>,
+		state_overrides: Option<BTreeMap<H160, CallStateOverride>>,
+	) -> Result<Bytes> {
+		let id = match frontier_backend_client::native_block_id::<B, C>(self.client.as_ref(), self.backend.as_ref(), number.clone())? {
+			Some(id) => id,
+			// There is no separate pending state, calls against it run on top of the best block.
+			None if matches!(number, Some(BlockNumber::Pending)) => BlockId::Hash(self.client.info().best_hash),
+			None => return Err(internal_err("header not found")),
+		};
+		let state_overrides = convert_state_overrides(state_overrides.unwrap_or_default())?;
 
 		let CallRequest {
 			from,
@@ -769,7 +1275,7 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		let gas_limit = match gas {
 			Some(amount) => amount,
 			None => {
-				let block = self.client.runtime_api().current_block(&BlockId::Hash(hash))
+				let block = self.client.runtime_api().current_block(&id)
 					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
 				if let Some(block) = block {
 					block.header.gas_limit
@@ -782,29 +1288,32 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 
 		match to {
 			Some(to) => {
-				let info = self.client.runtime_api()
-					.call(
-						&BlockId::Hash(hash),
-						from.unwrap_or_default(),
-						to,
-						data,
-						value.unwrap_or_default(),
-						gas_limit,
-						gas_price,
-						nonce,
-						false,
-					)
-					.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-					.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
+				let info = runtime_call::<B, C>(
+					self.client.as_ref(),
+					&id,
+					from.unwrap_or_default(),
+					to,
+					data,
+					value.unwrap_or_default(),
+					gas_limit,
+					gas_price,
+					nonce,
+					false,
+					state_overrides,
+				)?;
 
 				error_on_execution_failure(&info.exit_reason, &info.value)?;
 
 				Ok(Bytes(info.value))
 			},
 			None => {
+				if !state_overrides.is_empty() {
+					return Err(internal_err("state overrides are not supported for contract creation"));
+				}
+
 				let info = self.client.runtime_api()
 					.create(
-						&BlockId::Hash(hash),
+						&id,
 						from.unwrap_or_default(),
 						data,
 						value.unwrap_or_default(),
@@ -823,64 +1332,75 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		}
 	}
 
-	fn estimate_gas(&self, request: CallRequest, _: Option<BlockNumber>) -> Result<U256> {
-		let calculate_gas_used = |request| -> Result<U256> {
-			let hash = self.client.info().best_hash;
+	fn estimate_gas(&self, request: CallRequest, number: Option<BlockNumber>) -> Result<U256> {
+		let id = match frontier_backend_client::native_block_id::<B, C>(self.client.as_ref(), self.backend.as_ref(), number.clone())? {
+			Some(id) => id,
+			// There is no separate pending state, calls against it run on top of the best block.
+			None if matches!(number, Some(BlockNumber::Pending)) => BlockId::Hash(self.client.info().best_hash),
+			None => return Err(internal_err("header not found")),
+		};
 
-			let CallRequest {
-				from,
-				to,
-				gas_price,
-				gas,
-				value,
-				data,
-				nonce
-			} = request;
+		let CallRequest {
+			from,
+			to,
+			gas_price,
+			gas,
+			value,
+			data,
+			nonce
+		} = request;
+		let from = from.unwrap_or_default();
+		let value = value.unwrap_or_default();
+		let data = data.map(|d| d.0).unwrap_or_default();
 
-			// use given gas limit or query current block's limit
-			let gas_limit = match gas {
-				Some(amount) => amount,
-				None => {
-					let block = self.client.runtime_api().current_block(&BlockId::Hash(hash))
-						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
-					if let Some(block) = block {
-						block.header.gas_limit
-					} else {
-						return Err(internal_err(format!("block unavailable, cannot query gas limit")));
-					}
-				},
-			};
+		// The estimate is bounded by the block gas limit and the given gas limit.
+		let block = self.client.runtime_api().current_block(&id)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		let mut highest = match block {
+			Some(block) => block.header.gas_limit,
+			None => return Err(internal_err("block unavailable, cannot query gas limit")),
+		};
+		if let Some(gas) = gas {
+			highest = highest.min(gas);
+		}
 
-			let data = data.map(|d| d.0).unwrap_or_default();
+		// And by the gas the sender can pay for.
+		if let Some(gas_price) = gas_price.filter(|gas_price| !gas_price.is_zero()) {
+			let balance = self.client.runtime_api().account_basic(&id, from)
+				.map_err(|err| internal_err(format!("fetch runtime account basic failed: {:?}", err)))?
+				.balance;
+			if value > balance {
+				return Err(internal_err("insufficient funds for transfer"));
+			}
+			highest = highest.min((balance - value) / gas_price);
+		}
 
-			let used_gas = match to {
+		let execute = |gas_limit: U256| -> Result<(ExtendExitReason, Vec<u8>, U256)> {
+			match to {
 				Some(to) => {
-					let info = self.client.runtime_api()
-						.call(
-							&BlockId::Hash(hash),
-							from.unwrap_or_default(),
-							to,
-							data,
-							value.unwrap_or_default(),
-							gas_limit,
-							gas_price,
-							nonce,
-							true,
-						)
-						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
-						.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
-
-					error_on_execution_failure(&info.exit_reason, &info.value)?;
+					let info = runtime_call::<B, C>(
+						self.client.as_ref(),
+						&id,
+						from,
+						to,
+						data.clone(),
+						value,
+						gas_limit,
+						gas_price,
+						nonce,
+						true,
+						Vec::new(),
+					)?;
 
-					info.used_gas
+					Ok((info.exit_reason, info.value, info.used_gas))
 				},
 				None => {
 					let info = self.client.runtime_api()
 						.create(
-							&BlockId::Hash(hash),
-							from.unwrap_or_default(),
-							data,
-							value.unwrap_or_default(),
+							&id,
+							from,
+							data.clone(),
+							value,
 							gas_limit,
 							gas_price,
 							nonce,
@@ -889,61 +1409,35 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 						.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
 						.map_err(|err| internal_err(format!("execution fatal: {:?}", err)))?;
 
-					error_on_execution_failure(&info.exit_reason, &[])?;
-
-					info.used_gas
+					Ok((info.exit_reason, Vec::new(), info.used_gas))
 				},
-			};
-
-			Ok(used_gas)
+			}
 		};
-		if cfg!(feature = "rpc_binary_search_estimate") {
-			let mut lower = U256::from(21_000);
-			// TODO: get a good upper limit, but below U64::max to operation overflow
-			let mut upper = U256::from(1_000_000_000);
-			let mut mid = upper;
-			let mut best = mid;
-			let mut old_best: U256;
-
-			// if the gas estimation depends on the gas limit, then we want to binary
-			// search until the change is under some threshold. but if not dependent,
-			// we want to stop immediately.
-			let mut change_pct = U256::from(100);
-			let threshold_pct = U256::from(10);
-
-			// invariant: lower <= mid <= upper
-			while change_pct > threshold_pct {
-				let mut test_request = request.clone();
-				test_request.gas = Some(mid);
-				match calculate_gas_used(test_request) {
-					// if Ok -- try to reduce the gas used
-					Ok(used_gas) => {
-						old_best = best;
-						best = used_gas;
-						change_pct = (U256::from(100) * (old_best - best)) / old_best;
-						upper = mid;
-						mid = (lower + upper + 1) / 2;
-					}
 
-					Err(err) => {
-						// if Err == OutofGas, we need more gas
-						if err.code == ErrorCode::ServerError(0) {
-							lower = mid;
-							mid = (lower + upper + 1) / 2;
-							if mid == lower {
-								break;
-							}
-						} else {
-							// Other errors, return directly
-							return Err(err);
-						}
-					}
-				}
+		// A failure other than running out of gas with the most gas possible does not
+		// depend on the gas limit, report it as is.
+		let (exit_reason, output, used_gas) = execute(highest)?;
+		if is_out_of_gas(&exit_reason) {
+			return Err(internal_err(format!("gas required exceeds allowance {}", highest)));
+		}
+		error_on_execution_failure(&exit_reason, &output)?;
+
+		// Execution can not succeed with less gas than it used, refunds aside.
+		let mut lowest = used_gas.saturating_sub(U256::one());
+
+		// invariant: execution fails with `lowest` and succeeds with `highest`
+		while lowest + 1 < highest {
+			let mid = (lowest + highest) / 2;
+			let (exit_reason, _, _) = execute(mid)?;
+			if is_success(&exit_reason) {
+				highest = mid;
+			} else {
+				// Reverts and errors below the gas that succeeded are caused by the lower limit.
+				lowest = mid;
 			}
-			Ok(best)
-		} else {
-			calculate_gas_used(request)
 		}
+
+		Ok(highest)
 	}
 
 	fn transaction_by_hash(&self, hash: H256) -> Result<Option<Transaction>> {
@@ -1066,61 +1560,30 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		let statuses = handler.current_transaction_statuses(&id);
 		let receipts = handler.current_receipts(&id);
 
+		match (block, statuses, receipts) {
+			(Some(block), Some(statuses), Some(receipts)) if index < receipts.len() => {
+				Ok(receipts_build(&block, &statuses, &receipts, index + 1).pop())
+			},
+			_ => Ok(None),
+		}
+	}
+
+	fn block_receipts(&self, number: BlockNumber) -> Result<Option<Vec<Receipt>>> {
+		let id = match frontier_backend_client::native_block_id::<B, C>(self.client.as_ref(), self.backend.as_ref(), Some(number))? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+		let handler = self.overrides.schemas.get(&schema).unwrap_or(&self.overrides.fallback);
+
+		let block = handler.current_block(&id);
+		let statuses = handler.current_transaction_statuses(&id);
+		let receipts = handler.current_receipts(&id);
+
 		match (block, statuses, receipts) {
 			(Some(block), Some(statuses), Some(receipts)) => {
-				let block_hash = H256::from_slice(
-					Keccak256::digest(&rlp::encode(&block.header)).as_slice()
-				);
-				let receipt = receipts[index].clone();
-				let status = statuses[index].clone();
-				let mut cumulative_receipts = receipts.clone();
-				cumulative_receipts.truncate((status.transaction_index + 1) as usize);
-
-				return Ok(Some(Receipt {
-					transaction_hash: Some(status.transaction_hash),
-					transaction_index: Some(status.transaction_index.into()),
-					block_hash: Some(block_hash),
-					from: Some(status.from),
-					to: status.to,
-					block_number: Some(block.header.number),
-					cumulative_gas_used: {
-						let cumulative_gas: u32 = cumulative_receipts.iter().map(|r| {
-							r.used_gas.as_u32()
-						}).sum();
-						U256::from(cumulative_gas)
-					},
-					gas_used: Some(receipt.used_gas),
-					contract_address: status.contract_address,
-					logs: {
-						let mut pre_receipts_log_index = None;
-						if cumulative_receipts.len() > 0 {
-							cumulative_receipts.truncate(cumulative_receipts.len() - 1);
-							pre_receipts_log_index = Some(cumulative_receipts.iter().map(|r| {
-								r.logs.len() as u32
-							}).sum::<u32>());
-						}
-						receipt.logs.iter().enumerate().map(|(i, log)| {
-							Log {
-								address: log.address,
-								topics: log.topics.clone(),
-								data: Bytes(log.data.clone()),
-								block_hash: Some(block_hash),
-								block_number: Some(block.header.number),
-								transaction_hash: Some(status.transaction_hash),
-								transaction_index: Some(status.transaction_index.into()),
-								log_index: Some(U256::from(
-									(pre_receipts_log_index.unwrap_or(0)) + i as u32
-								)),
-								transaction_log_index: Some(U256::from(i)),
-								removed: false,
-							}
-						}).collect()
-					},
-					status_code: Some(U64::from(receipt.state_root.to_low_u64_be())),
-					logs_bloom: receipt.logs_bloom,
-					state_root: None,
-				}))
-			}
+				Ok(Some(receipts_build(&block, &statuses, &receipts, receipts.len())))
+			},
 			_ => Ok(None),
 		}
 	}
@@ -1159,7 +1622,7 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 			let best_number = self.client.info().best_number;
 			let mut current_number = filter
 				.to_block.clone()
-				.and_then(|v| v.to_min_block_num())
+				.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
 				.map(|s| s.unique_saturated_into())
 				.unwrap_or(best_number);
 
@@ -1168,7 +1631,7 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 			}
 
 			let from_number = filter.from_block.clone()
-				.and_then(|v| v.to_min_block_num())
+				.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
 				.map(|s| s.unique_saturated_into())
 				.unwrap_or(
 					self.client.info().best_number
@@ -1176,6 +1639,7 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 
 			let _ = filter_range_logs(
 				self.client.as_ref(),
+				self.backend.as_ref(),
 				&self.overrides,
 				&mut ret,
 				self.max_past_logs,
@@ -1187,6 +1651,49 @@ impl<B, C, P, CT, BE, H: ExHashT> EthApiT for EthApi<B, C, P, CT, BE, H> where
 		Ok(ret)
 	}
 
+	fn logs_paged(&self, filter: Filter, cursor: Option<LogCursor>, page_size: Option<U256>) -> Result<LogsPage> {
+		if filter.block_hash.is_some() {
+			return Err(internal_err("blockHash is not supported by paged log queries, use eth_getLogs"));
+		}
+		let page_size = match page_size {
+			Some(page_size) if page_size.is_zero() => return Err(internal_err("page size must be positive")),
+			Some(page_size) => std::cmp::min(page_size, U256::from(self.max_past_logs)).as_usize(),
+			None => self.max_past_logs as usize,
+		};
+
+		let best_number = UniqueSaturatedInto::<u64>::unique_saturated_into(self.client.info().best_number);
+		let to_number = filter.to_block.clone()
+			.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
+			.map_or(best_number, |number| std::cmp::min(number, best_number));
+		let mut from_number = filter.from_block.clone()
+			.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
+			.unwrap_or(best_number);
+
+		let mut first_log_index = 0;
+		if let Some(cursor) = cursor {
+			if cursor.block_number < U256::from(from_number) || cursor.block_number > U256::from(to_number) {
+				return Err(internal_err("cursor is outside of the filter range"));
+			}
+			from_number = cursor.block_number.as_u64();
+			first_log_index = std::cmp::min(cursor.log_index, U256::from(u32::max_value())).as_u32();
+		}
+
+		if from_number > to_number {
+			return Ok(LogsPage { logs: Vec::new(), cursor: None });
+		}
+
+		filter_logs_page(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			&self.overrides,
+			&filter,
+			from_number,
+			to_number,
+			first_log_index,
+			page_size,
+		)
+	}
+
 	fn work(&self) -> Result<Work> {
 		Ok(Work {
 			pow_hash: H256::default(),
@@ -1303,6 +1810,7 @@ impl<B, C> Web3ApiT for Web3Api<B, C> where
 
 pub struct EthFilterApi<B: BlockT, C, BE> {
 	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
 	filter_pool: FilterPool,
 	max_stored_filters: usize,
 	overrides: Arc<OverrideHandle<B>>,
@@ -1320,6 +1828,7 @@ impl<B: BlockT, C, BE> EthFilterApi<B, C, BE>  where
 {
 	pub fn new(
 		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
 		filter_pool: FilterPool,
 		max_stored_filters: usize,
 		overrides: Arc<OverrideHandle<B>>,
@@ -1327,6 +1836,7 @@ impl<B: BlockT, C, BE> EthFilterApi<B, C, BE>  where
 	) -> Self {
 		Self {
 			client: client.clone(),
+			backend,
 			filter_pool,
 			max_stored_filters,
 			overrides,
@@ -1388,7 +1898,7 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 	BE::State: StateBackend<BlakeTwo256>,
 {
 	fn new_filter(&self, filter: Filter) -> Result<U256> {
-		self.create_filter(FilterType::Log(filter))
+		self.create_filter(FilterType::Log(filter, self.client.info().best_hash))
 	}
 
 	fn new_block_filter(&self) -> Result<U256> {
@@ -1396,7 +1906,7 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 	}
 
 	fn new_pending_transaction_filter(&self) -> Result<U256> {
-		Err(internal_err("Method not available."))
+		self.create_filter(FilterType::PendingTransaction(Vec::new()))
 	}
 
 	fn filter_changes(&self, index: Index) -> Result<FilterChanges> {
@@ -1437,13 +1947,28 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 						);
 						Ok(FilterChanges::Hashes(ethereum_hashes))
 					},
+					// Ethereum transactions imported into the pool since last poll.
+					FilterType::PendingTransaction(imported) => {
+						let ethereum_hashes = imported.clone();
+						// Update filter `last_poll` and drain the collected hashes.
+						locked.insert(
+							key,
+							FilterPoolItem {
+								last_poll: BlockNumber::Num(block_number + 1),
+								filter_type: FilterType::PendingTransaction(Vec::new()),
+								at_block: pool_item.at_block
+							}
+						);
+						Ok(FilterChanges::Hashes(ethereum_hashes))
+					},
 					// For each event since last poll, get a vector of ethereum logs.
-					FilterType::Log(filter) => {
+					FilterType::Log(filter, last_poll_hash) => {
 						// Either the filter-specific `to` block or best block.
 						let best_number = self.client.info().best_number;
+						let best_hash = self.client.info().best_hash;
 						let mut current_number = filter
 							.to_block.clone()
-							.and_then(|v| v.to_min_block_num())
+							.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
 							.map(|s| s.unique_saturated_into())
 							.unwrap_or(best_number);
 
@@ -1458,18 +1983,44 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 							.unique_saturated_into();
 
 						let filter_from = filter.from_block.clone()
-							.and_then(|v| v.to_min_block_num())
+							.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
 							.map(|s| s.unique_saturated_into())
 							.unwrap_or(
 								last_poll
 							);
 
-						let from_number = std::cmp::max(last_poll, filter_from);
+						let mut from_number = std::cmp::max(last_poll, filter_from);
 
 						// Build the response.
 						let mut ret: Vec<Log> = Vec::new();
+
+						// Blocks reported by previous polls may have been retracted since, their
+						// logs are reported as removed and the new chain is reported from the
+						// common block on.
+						if *last_poll_hash != best_hash {
+							let first_reported = std::cmp::max(
+								pool_item.at_block.unique_saturated_into(),
+								filter_from,
+							);
+							let common_number = retracted_logs(
+								self.client.as_ref(),
+								&self.overrides,
+								&mut ret,
+								&filter,
+								*last_poll_hash,
+								best_hash,
+								first_reported,
+								current_number,
+							)?;
+							from_number = std::cmp::max(
+								std::cmp::min(from_number, common_number.saturating_add(One::one())),
+								filter_from,
+							);
+						}
+
 						let _ = filter_range_logs(
 							self.client.as_ref(),
+							self.backend.as_ref(),
 							&self.overrides,
 							&mut ret,
 							self.max_past_logs,
@@ -1484,16 +2035,12 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 								last_poll: BlockNumber::Num(
 									block_number + 1
 								),
-								filter_type: pool_item.clone().filter_type,
+								filter_type: FilterType::Log(filter.clone(), best_hash),
 								at_block: pool_item.at_block
 							}
 						);
 						Ok(FilterChanges::Logs(ret))
 					},
-					// Should never reach here.
-					_ => {
-						Err(internal_err("Method not available."))
-					}
 				}
 			} else {
 				Err(internal_err(format!("Filter id {:?} does not exist.", key)))
@@ -1512,11 +2059,11 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 			// Try to get key.
 			if let Some(pool_item) = locked.clone().get(&key) {
 				match &pool_item.filter_type {
-					FilterType::Log(filter) => {
+					FilterType::Log(filter, _) => {
 						let best_number = self.client.info().best_number;
 						let mut current_number = filter
 							.to_block.clone()
-							.and_then(|v| v.to_min_block_num())
+							.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
 							.map(|s| s.unique_saturated_into())
 							.unwrap_or(best_number);
 
@@ -1529,7 +2076,7 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 						}
 
 						let from_number = filter.from_block.clone()
-							.and_then(|v| v.to_min_block_num())
+							.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
 							.map(|s| s.unique_saturated_into())
 							.unwrap_or(
 								self.client.info().best_number
@@ -1538,6 +2085,7 @@ impl<B, C, BE> EthFilterApiT for EthFilterApi<B, C, BE> where
 						let mut ret: Vec<Log> = Vec::new();
 						let _ = filter_range_logs(
 							self.client.as_ref(),
+							self.backend.as_ref(),
 							&self.overrides,
 							&mut ret,
 							self.max_past_logs,
@@ -1619,6 +2167,64 @@ where
 		}
 	}
 
+	/// Collects the Ethereum transactions imported into the transaction pool
+	/// into every pending transaction filter, until the filter is next polled.
+	pub async fn pending_transaction_filter_task<P>(
+		client: Arc<C>,
+		pool: Arc<P>,
+		filter_pool: Arc<Mutex<BTreeMap<U256, FilterPoolItem>>>,
+	) where
+		C: HeaderBackend<B>,
+		C::Api: TxPoolRuntimeApi<B>,
+		P: TransactionPool<Block=B>,
+	{
+		let mut notification_st = pool.import_notification_stream();
+
+		while let Some(hash) = notification_st.next().await {
+			// Filtering needs a runtime call, skipped while nobody polls for pending transactions.
+			let is_polled = match filter_pool.lock() {
+				Ok(filter_pool) => filter_pool.values()
+					.any(|item| matches!(item.filter_type, FilterType::PendingTransaction(_))),
+				Err(_) => false,
+			};
+			if !is_polled {
+				continue;
+			}
+			let extrinsic = match pool.ready_transaction(&hash) {
+				Some(tx) => tx.data().clone(),
+				// Already included or dropped.
+				None => continue,
+			};
+			let id = BlockId::Hash(client.info().best_hash);
+			let transactions = match client.runtime_api().extrinsic_filter(&id, vec![extrinsic]) {
+				Ok(transactions) => transactions,
+				Err(err) => {
+					log::warn!(target: "rpc", "Failed to filter imported extrinsic {:?}: {:?}", hash, err);
+					continue;
+				},
+			};
+			if transactions.is_empty() {
+				continue;
+			}
+			let ethereum_hashes: Vec<H256> = transactions.iter().map(|transaction| {
+				H256::from_slice(Keccak256::digest(&rlp::encode(transaction)).as_slice())
+			}).collect();
+
+			if let Ok(filter_pool) = &mut filter_pool.lock() {
+				for item in filter_pool.values_mut() {
+					if let FilterType::PendingTransaction(imported) = &mut item.filter_type {
+						// Kept in arrival order, a transaction imported again is reported once.
+						for hash in ethereum_hashes.iter() {
+							if !imported.contains(hash) {
+								imported.push(*hash);
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+
 	pub async fn filter_pool_task(
 		client: Arc<C>,
 		filter_pool: Arc<Mutex<BTreeMap<U256, FilterPoolItem>>>,
@@ -1654,3 +2260,80 @@ where
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// Blocks 1 to 4 holding 3, 0, 2 and 1 logs.
+	fn chain() -> Vec<(u64, Vec<Log>)> {
+		[(1, 3), (2, 0), (3, 2), (4, 1)].iter().map(|&(number, count)| {
+			let logs = (0..count).map(|log_index| Log {
+				address: H160::zero(),
+				topics: Vec::new(),
+				data: Bytes(Vec::new()),
+				block_hash: None,
+				block_number: Some(U256::from(number)),
+				transaction_hash: None,
+				transaction_index: None,
+				log_index: Some(U256::from(log_index)),
+				transaction_log_index: None,
+				removed: false,
+			}).collect();
+			(number, logs)
+		}).collect()
+	}
+
+	fn position(log: &Log) -> (u64, u64) {
+		(log.block_number.unwrap().as_u64(), log.log_index.unwrap().as_u64())
+	}
+
+	fn logs_page(blocks: Vec<(u64, Vec<Log>)>, from: u64, first_log_index: u32, page_size: usize) -> LogsPage {
+		let blocks = blocks.into_iter().filter(move |(number, _)| *number >= from);
+		collect_logs_page(blocks, |logs| logs, from, first_log_index, page_size, || false)
+	}
+
+	#[test]
+	fn logs_page_resumes_from_the_cursor() {
+		let mut cursor = Some(LogCursor { block_number: U256::from(1), log_index: U256::zero() });
+		let mut positions = Vec::new();
+		let mut pages = 0;
+		while let Some(LogCursor { block_number, log_index }) = cursor {
+			let page = logs_page(chain(), block_number.as_u64(), log_index.as_u32(), 2);
+			assert!(page.logs.len() <= 2);
+			positions.extend(page.logs.iter().map(position));
+			cursor = page.cursor;
+			pages += 1;
+		}
+
+		assert_eq!(pages, 3);
+		assert_eq!(positions, vec![(1, 0), (1, 1), (1, 2), (3, 0), (3, 1), (4, 0)]);
+	}
+
+	#[test]
+	fn logs_page_cursor_points_at_the_next_log() {
+		let first = logs_page(chain(), 1, 0, 4);
+		assert_eq!(first.logs.len(), 4);
+		assert_eq!(first.cursor, Some(LogCursor { block_number: U256::from(3), log_index: U256::from(1) }));
+
+		let last = logs_page(chain(), 3, 1, 4);
+		assert_eq!(last.logs.iter().map(position).collect::<Vec<_>>(), vec![(3, 1), (4, 0)]);
+		assert_eq!(last.cursor, None);
+	}
+
+	#[test]
+	fn logs_page_skips_only_in_the_cursor_block() {
+		// The index may only return the blocks holding matching logs, the first of them is
+		// after the cursor block and none of its logs were served.
+		let blocks: Vec<_> = chain().into_iter().filter(|(number, _)| *number >= 3).collect();
+		let page = collect_logs_page(blocks.into_iter(), |logs| logs, 2, 1, 10, || false);
+		assert_eq!(page.logs.iter().map(position).collect::<Vec<_>>(), vec![(3, 0), (3, 1), (4, 0)]);
+	}
+
+	#[test]
+	fn logs_page_ends_at_a_block_boundary_when_expired() {
+		let page = collect_logs_page(chain().into_iter(), |logs| logs, 1, 0, 10, || true);
+		assert_eq!(page.logs.iter().map(position).collect::<Vec<_>>(), vec![(1, 0), (1, 1), (1, 2)]);
+		assert_eq!(page.cursor, Some(LogCursor { block_number: U256::from(2), log_index: U256::zero() }));
+	}
+}
@@ -44,21 +44,23 @@ use jsonrpc_pubsub::{
 use fc_rpc_core::EthPubSubApi::{self as EthPubSubApiT};
 use fc_rpc_core::types::{
 	Rich, Header, Bytes, Log, FilteredParams,
-	pubsub::{Kind, Params, Result as PubSubResult, PubSubSyncStatus}
+	pubsub::{Kind, Params, Result as PubSubResult, PubSubSyncStatus, PendingTransactionsFilter}
 };
 use ethereum_types::{H256, U256};
-use codec::Decode;
+use codec::DecodeAll;
 use sha3::{Keccak256, Digest};
 
 pub use fc_rpc_core::EthPubSubApiServer;
 use futures::{StreamExt as _, TryStreamExt as _};
 
 use jsonrpc_core::{Result as JsonRpcResult, futures::{Future, Sink}};
-use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus};
+use fp_rpc::{EthereumRuntimeRPCApi, TransactionStatus, EIP658Receipt};
 
 use sc_network::{NetworkService, ExHashT};
+use sc_rpc::system::Request as SystemRequest;
+use sp_utils::mpsc::TracingUnboundedSender;
 
-use crate::{frontier_backend_client, overrides::OverrideHandle};
+use crate::{frontier_backend_client, overrides::OverrideHandle, eth::transaction_build, network_sync_info};
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub struct HexEncodedIdProvider {
@@ -88,6 +90,7 @@ pub struct EthPubSubApi<B: BlockT, P, C, BE, H: ExHashT> {
 	_pool: Arc<P>,
 	client: Arc<C>,
 	network: Arc<NetworkService<B, H>>,
+	system_rpc_tx: TracingUnboundedSender<SystemRequest<B>>,
 	subscriptions: SubscriptionManager<HexEncodedIdProvider>,
 	overrides: Arc<OverrideHandle<B>>,
 	_marker: PhantomData<(B, BE)>,
@@ -103,6 +106,7 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApi<B, P, C, BE, H> where
 		_pool: Arc<P>,
 		client: Arc<C>,
 		network: Arc<NetworkService<B, H>>,
+		system_rpc_tx: TracingUnboundedSender<SystemRequest<B>>,
 		subscriptions: SubscriptionManager<HexEncodedIdProvider>,
 		overrides: Arc<OverrideHandle<B>>,
 	) -> Self {
@@ -110,6 +114,7 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApi<B, P, C, BE, H> where
 			_pool,
 			client: client.clone(),
 			network,
+			system_rpc_tx,
 			subscriptions,
 			overrides,
 			_marker: PhantomData
@@ -160,7 +165,7 @@ impl SubscriptionResult {
 	pub fn logs(
 		&self,
 		block: ethereum::Block,
-		receipts: Vec<ethereum::Receipt>,
+		receipts: Vec<EIP658Receipt>,
 		params: &FilteredParams
 	) -> Vec<Log> {
 		let block_hash = Some(H256::from_slice(
@@ -240,6 +245,17 @@ impl SubscriptionResult {
 	}
 }
 
+/// Decode the transactions of `pallet_ethereum::Pending`, which holds EIP-658 receipts since
+/// storage schema V2 and legacy receipts before it.
+fn pending_transactions_decode(data: &[u8]) -> Vec<ethereum::Transaction> {
+	if let Ok(pending) = Vec::<(ethereum::Transaction, TransactionStatus, EIP658Receipt)>::decode_all(data) {
+		return pending.into_iter().map(|(transaction, _, _)| transaction).collect();
+	}
+	Vec::<(ethereum::Transaction, TransactionStatus, ethereum::Receipt)>::decode_all(data)
+		.map(|pending| pending.into_iter().map(|(transaction, _, _)| transaction).collect())
+		.unwrap_or_default()
+}
+
 fn storage_prefix_build(module: &[u8], storage: &[u8]) -> Vec<u8> {
 	[twox_128(module), twox_128(storage)].concat().to_vec()
 }
@@ -264,13 +280,15 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApiT for EthPubSubApi<B, P, C, BE
 		kind: Kind,
 		params: Option<Params>,
 	) {
-		let filtered_params = match params {
-			Some(Params::Logs(filter)) => FilteredParams::new(Some(filter)),
-			_ => FilteredParams::default()
+		let (filtered_params, pending_filter) = match params {
+			Some(Params::Logs(filter)) => (FilteredParams::new(Some(filter)), PendingTransactionsFilter::default()),
+			Some(Params::PendingTransactions(filter)) => (FilteredParams::default(), filter),
+			_ => (FilteredParams::default(), PendingTransactionsFilter::default()),
 		};
 
 		let client = self.client.clone();
 		let network = self.network.clone();
+		let system_rpc_tx = self.system_rpc_tx.clone();
 		let overrides = self.overrides.clone();
 		match kind {
 			Kind::Logs => {
@@ -278,31 +296,47 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApiT for EthPubSubApi<B, P, C, BE
 					let stream = client.import_notification_stream()
 					.filter_map(move |notification| {
 						if notification.is_new_best {
-							let id = BlockId::Hash(notification.hash);
+							let block_logs = |hash: B::Hash| -> Vec<Log> {
+								let id = BlockId::Hash(hash);
 
-							let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
-								client.as_ref(), id
-							);
-							let handler = overrides.schemas.get(&schema).unwrap_or(&overrides.fallback);
+								let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
+									client.as_ref(), id
+								);
+								let handler = overrides.schemas.get(&schema).unwrap_or(&overrides.fallback);
 
-							let block = handler.current_block(&id);
-							let receipts = handler.current_receipts(&id);
+								let block = handler.current_block(&id);
+								let receipts = handler.current_receipts(&id);
 
-							match (receipts, block) {
-								(Some(receipts), Some(block)) =>
-									futures::future::ready(Some((block, receipts))),
-								_ => futures::future::ready(None)
+								match (receipts, block) {
+									(Some(receipts), Some(block)) =>
+										SubscriptionResult::new().logs(block, receipts, &filtered_params),
+									_ => Vec::new(),
+								}
+							};
+
+							let mut logs = Vec::new();
+							// On a reorg, the logs of the retracted blocks are sent again marked as
+							// removed, from the former best block down, followed by the logs of the
+							// blocks enacted before the new best block.
+							if let Some(tree_route) = &notification.tree_route {
+								for retracted in tree_route.retracted() {
+									logs.extend(block_logs(retracted.hash).into_iter().map(|mut log| {
+										log.removed = true;
+										log
+									}));
+								}
+								for enacted in tree_route.enacted() {
+									logs.extend(block_logs(enacted.hash));
+								}
 							}
+							logs.extend(block_logs(notification.hash));
+
+							futures::future::ready(Some(logs))
 						} else {
 							futures::future::ready(None)
 						}
 					})
-					.flat_map(move |(block, receipts)| {
-						futures::stream::iter(
-							SubscriptionResult::new()
-								.logs(block, receipts, &filtered_params)
-						)
-					})
+					.flat_map(futures::stream::iter)
 					.map(|x| {
 						return Ok::<Result<
 							PubSubResult,
@@ -353,6 +387,35 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApiT for EthPubSubApi<B, P, C, BE
 						.map(|_| ())
 				});
 			},
+			Kind::NewFinalizedHeads => {
+				self.subscriptions.add(subscriber, |sink| {
+					let stream = client.finality_notification_stream()
+					.filter_map(move |notification| {
+						let id = BlockId::Hash(notification.hash);
+
+						let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(
+							client.as_ref(), id
+						);
+						let handler = overrides.schemas.get(&schema).unwrap_or(&overrides.fallback);
+
+						let block = handler.current_block(&id);
+						futures::future::ready(block)
+					})
+					.map(|block| {
+						return Ok::<_, ()>(Ok(
+							SubscriptionResult::new()
+								.new_heads(block)
+						));
+					})
+					.compat();
+					sink
+						.sink_map_err(|e| warn!(
+							"Error sending notifications: {:?}", e
+						))
+						.send_all(stream)
+						.map(|_| ())
+				});
+			},
 			Kind::NewPendingTransactions => {
 				if let Ok(stream) = client.storage_changes_notification_stream(
 					Some(&[StorageKey(
@@ -372,29 +435,36 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApiT for EthPubSubApi<B, P, C, BE
 								}).collect();
 							for change in storage {
 								if let Some(data) = change {
-									let storage: Vec<(
-										ethereum::Transaction,
-										TransactionStatus,
-										ethereum::Receipt
-									)> = Decode::decode(&mut &data.0[..]).unwrap();
-									let tmp: Vec<ethereum::Transaction> =
-										storage.iter().map(|x| x.0.clone()).collect();
-									transactions.extend(tmp);
+									transactions.extend(pending_transactions_decode(&data.0));
 								}
 							}
 							futures::stream::iter(transactions)
 						})
-						.map(|transaction| {
-							return Ok::<Result<
-								PubSubResult,
-								jsonrpc_core::types::error::Error
-							>, ()>(Ok(
-								PubSubResult::TransactionHash(H256::from_slice(
+						.filter_map(move |transaction| {
+							// The sender is only recovered when needed.
+							let result = if pending_filter.full_transactions || pending_filter.has_addresses() {
+								let transaction = transaction_build(transaction, None, None);
+								if !pending_filter.matches(&transaction.from, transaction.to.as_ref()) {
+									None
+								} else if pending_filter.full_transactions {
+									Some(PubSubResult::Transaction(Box::new(transaction)))
+								} else {
+									Some(PubSubResult::TransactionHash(transaction.hash))
+								}
+							} else {
+								Some(PubSubResult::TransactionHash(H256::from_slice(
 									Keccak256::digest(
 										&rlp::encode(&transaction)
 									).as_slice()
-								))
-							));
+								)))
+							};
+							futures::future::ready(result)
+						})
+						.map(|result| {
+							return Ok::<Result<
+								PubSubResult,
+								jsonrpc_core::types::error::Error
+							>, ()>(Ok(result));
 						})
 						.compat();
 
@@ -420,14 +490,23 @@ impl<B: BlockT, P, C, BE, H: ExHashT> EthPubSubApiT for EthPubSubApi<B, P, C, BE
 							futures::future::ready(None)
 						}
 					})
-					.map(|syncing| {
+					.then(move |syncing| {
+						let system_rpc_tx = system_rpc_tx.clone();
+						Box::pin(async move {
+							let status = if syncing {
+								network_sync_info(system_rpc_tx).await
+							} else {
+								None
+							};
+							PubSubSyncStatus { syncing, status }
+						})
+					})
+					.map(|sync_status| {
 						return Ok::<Result<
 							PubSubResult,
 							jsonrpc_core::types::error::Error
 						>, ()>(Ok(
-							PubSubResult::SyncState(PubSubSyncStatus {
-								syncing: syncing
-							})
+							PubSubResult::SyncState(sync_status)
 						));
 					})
 					.compat();
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{marker::PhantomData, sync::Arc};
+use ethereum_types::{H256, U256};
+use jsonrpc_core::Result;
+use sp_runtime::traits::{Block as BlockT, UniqueSaturatedInto};
+use sp_blockchain::HeaderBackend;
+use fc_rpc_core::FrontierApi as FrontierApiT;
+use fc_rpc_core::types::{MappingSyncStatus, MappedBlock};
+use fc_mapping_sync::SharedMappingLag;
+use crate::internal_err;
+
+pub use fc_rpc_core::FrontierApiServer;
+
+pub struct FrontierApi<B: BlockT, C> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	mapping_lag: SharedMappingLag<B>,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C> FrontierApi<B, C> {
+	pub fn new(client: Arc<C>, backend: Arc<fc_db::Backend<B>>, mapping_lag: SharedMappingLag<B>) -> Self {
+		Self {
+			client,
+			backend,
+			mapping_lag,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C> FrontierApiT for FrontierApi<B, C> where
+	C: HeaderBackend<B> + Send + Sync + 'static,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+{
+	fn sync_status(&self) -> Result<MappingSyncStatus> {
+		let syncing_tips = self.backend.meta().current_syncing_tips().map_err(internal_err)?;
+		let lag = fc_mapping_sync::cached_mapping_lag(self.client.as_ref(), self.backend.as_ref(), &self.mapping_lag)
+			.map_err(internal_err)?;
+
+		Ok(MappingSyncStatus {
+			latest_mapped_block: lag.latest_mapped.map(|(number, hash)| MappedBlock {
+				number: U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(number)),
+				hash,
+			}),
+			syncing_tips,
+			unmapped_blocks: U256::from(lag.unmapped_blocks),
+			unmapped_blocks_capped: lag.capped,
+		})
+	}
+}
@@ -16,18 +16,26 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+mod debug;
 mod eth;
 mod eth_pubsub;
+mod frontier;
 mod overrides;
+mod trace;
+mod txpool;
 
+pub use debug::{DebugApi, DebugApiServer};
 pub use eth::{
 	EthApi, EthApiServer, EthFilterApi, EthFilterApiServer, NetApi, NetApiServer, Web3Api, Web3ApiServer,
 	EthTask,
 };
 pub use eth_pubsub::{EthPubSubApi, EthPubSubApiServer, HexEncodedIdProvider};
-pub use overrides::{StorageOverride, SchemaV1Override, OverrideHandle, RuntimeApiStorageOverride};
+pub use frontier::{FrontierApi, FrontierApiServer};
+pub use trace::{TraceApi, TraceApiServer};
+pub use txpool::{TxPoolApi, TxPoolApiServer};
+pub use overrides::{StorageOverride, SchemaV1Override, SchemaV2Override, OverrideHandle, RuntimeApiStorageOverride};
 
-use ethereum_types::{H160, H256};
+use ethereum_types::{H160, H256, U256};
 use ethereum::{
 	Transaction as EthereumTransaction, TransactionMessage as EthereumTransactionMessage,
 };
@@ -36,6 +44,10 @@ use rustc_hex::ToHex;
 use pallet_vm::{ExtendExitReason, ExitReason, EVMCStatusCode};
 use sha3::{Digest, Keccak256};
 use evm::ExitError;
+use sp_runtime::traits::{Block as BlockT, NumberFor, UniqueSaturatedInto};
+use sp_utils::mpsc::TracingUnboundedSender;
+use sc_rpc::system::Request as SystemRequest;
+use fc_rpc_core::types::SyncInfo;
 
 pub mod frontier_backend_client {
 
@@ -45,8 +57,8 @@ pub mod frontier_backend_client {
 	use sp_storage::StorageKey;
 	use sp_blockchain::HeaderBackend;
 	use sp_api::{BlockId, HeaderT};
-	use sc_client_api::backend::{StorageProvider, Backend, StateBackend};
-	use fc_rpc_core::types::BlockNumber;
+	use sc_client_api::{backend::{StorageProvider, Backend, StateBackend}, ProofProvider};
+	use fc_rpc_core::types::{BlockNumber, Bytes};
 	use fp_storage::PALLET_ETHEREUM_SCHEMA;
 
 	use jsonrpc_core::Result as RpcResult;
@@ -78,10 +90,28 @@ pub mod frontier_backend_client {
 			},
 			BlockNumber::Pending => {
 				None
-			}
+			},
+			BlockNumber::Finalized | BlockNumber::Safe => {
+				Some(BlockId::Hash(
+					client.info().finalized_hash
+				))
+			},
 		})
 	}
 
+	/// Resolve a bound of a block range to a number. Tags other than `finalized` and `safe`
+	/// resolve to none.
+	pub fn range_block_number<B: BlockT, C>(client: &C, number: &BlockNumber) -> Option<u64> where
+		C: HeaderBackend<B>,
+	{
+		match number {
+			BlockNumber::Finalized | BlockNumber::Safe => Some(
+				UniqueSaturatedInto::<u64>::unique_saturated_into(client.info().finalized_number)
+			),
+			number => number.to_min_block_num(),
+		}
+	}
+
 	pub fn load_hash<B: BlockT>(backend: &fc_db::Backend<B>, hash: H256) -> RpcResult<Option<BlockId<B>>> where
 		B: BlockT,
 		B: BlockT<Hash=H256> + Send + Sync + 'static,
@@ -134,28 +164,44 @@ pub mod frontier_backend_client {
 		let transaction_metadata = backend.mapping().transaction_metadata(&transaction_hash)
 			.map_err(|err| internal_err(format!("fetch aux store failed: {:?}", err)))?;
 
-			if transaction_metadata.len() == 1 {
-				Ok(Some((
-					transaction_metadata[0].ethereum_block_hash,
-					transaction_metadata[0].ethereum_index,
-				)))
-			} else if transaction_metadata.len() > 1 {
-				transaction_metadata
-					.iter()
-					.find(|meta| is_canon::<B, C>(client, meta.block_hash))
-					.map_or(
-						Ok(Some((
-							transaction_metadata[0].ethereum_block_hash,
-							transaction_metadata[0].ethereum_index,
-						))),
-						|meta| Ok(Some((meta.ethereum_block_hash, meta.ethereum_index))),
-					)
-			} else {
-				Ok(None)
-			}
+		// The mapping keeps the transaction for every block it was synced in, only the one on the
+		// canonical chain is returned.
+		Ok(transaction_metadata
+			.iter()
+			.find(|meta| is_canon::<B, C>(client, meta.block_hash))
+			.map(|meta| (meta.ethereum_block_hash, meta.ethereum_index)))
+	}
+
+	/// Trie nodes proving the values stored under `keys` at `id`, as served by `eth_getProof`.
+	pub fn read_proof<B: BlockT, C>(client: &C, id: &BlockId<B>, keys: &[&[u8]]) -> RpcResult<Vec<Bytes>> where
+		C: ProofProvider<B>,
+	{
+		let proof = client.read_proof(id, &mut keys.iter().cloned())
+			.map_err(|err| internal_err(format!("read proof failed: {:?}", err)))?;
+		Ok(proof.iter_nodes().map(Bytes).collect())
 	}
 }
 
+/// Sync progress of the network, requested from the network worker through the channel of the
+/// system RPC.
+pub(crate) async fn network_sync_info<B: BlockT>(
+	system_rpc_tx: TracingUnboundedSender<SystemRequest<B>>,
+) -> Option<SyncInfo> {
+	let (sender, receiver) = futures::channel::oneshot::channel();
+	system_rpc_tx.unbounded_send(SystemRequest::SyncState(sender)).ok()?;
+	let state = receiver.await.ok()?;
+
+	let to_u256 = |number: NumberFor<B>| U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(number));
+	Some(SyncInfo {
+		starting_block: to_u256(state.starting_block),
+		current_block: to_u256(state.current_block),
+		highest_block: to_u256(state.highest_block.unwrap_or(state.current_block)),
+		warp_chunks_amount: None,
+		warp_chunks_processed: None,
+		mapping_lag: None,
+	})
+}
+
 pub fn internal_err<T: ToString>(message: T) -> Error {
 	Error {
 		code: ErrorCode::InternalError,
@@ -164,17 +210,30 @@ pub fn internal_err<T: ToString>(message: T) -> Error {
 	}
 }
 
+/// Whether an execution succeeded, on either VM.
+pub fn is_success(reason: &ExtendExitReason) -> bool {
+	matches!(
+		reason,
+		ExtendExitReason::ExitReason(ExitReason::Succeed(_)) |
+		ExtendExitReason::EVMCStatusCode(EVMCStatusCode::EvmcSuccess)
+	)
+}
+
+/// Whether an execution ran out of gas, on either VM.
+pub fn is_out_of_gas(reason: &ExtendExitReason) -> bool {
+	matches!(
+		reason,
+		ExtendExitReason::ExitReason(ExitReason::Error(ExitError::OutOfGas)) |
+		ExtendExitReason::EVMCStatusCode(EVMCStatusCode::EvmcOutOfGas)
+	)
+}
+
 pub fn error_on_execution_failure(reason: &ExtendExitReason, data: &[u8]) -> Result<(), Error> {
 	match reason {
 		ExtendExitReason::ExitReason(ExitReason::Succeed(_)) => Ok(()),
 		ExtendExitReason::ExitReason(ExitReason::Error(e)) => {
 			if *e == ExitError::OutOfGas {
-				// `ServerError(0)` will be useful in estimate gas
-				return Err(Error {
-					code: ErrorCode::ServerError(0),
-					message: format!("out of gas"),
-					data: None,
-				});
+				return Err(internal_err("out of gas"));
 			}
 			Err(Error {
 				code: ErrorCode::InternalError,
@@ -182,23 +241,7 @@ pub fn error_on_execution_failure(reason: &ExtendExitReason, data: &[u8]) -> Res
 				data: Some(Value::String("0x".to_string()))
 			})
 		},
-		ExtendExitReason::ExitReason(ExitReason::Revert(_)) => {
-			let mut message = "VM Exception while processing transaction: revert".to_string();
-			// A minimum size of error function selector (4) + offset (32) + string length (32)
-			// should contain a utf-8 encoded revert reason.
-			if data.len() > 68 {
-				let message_len = data[36..68].iter().sum::<u8>();
-				let body: &[u8] = &data[68..68 + message_len as usize];
-				if let Ok(reason) = std::str::from_utf8(body) {
-					message = format!("{} {}", message, reason.to_string());
-				}
-			}
-			Err(Error {
-				code: ErrorCode::InternalError,
-				message,
-				data: Some(Value::String(data.to_hex()))
-			})
-		},
+		ExtendExitReason::ExitReason(ExitReason::Revert(_)) => Err(revert_error(data)),
 		ExtendExitReason::ExitReason(ExitReason::Fatal(e)) => {
 			Err(Error {
 				code: ErrorCode::InternalError,
@@ -209,6 +252,8 @@ pub fn error_on_execution_failure(reason: &ExtendExitReason, data: &[u8]) -> Res
 		ExtendExitReason::EVMCStatusCode(status) => {
 			match status {
 				EVMCStatusCode::EvmcSuccess => Ok(()),
+				EVMCStatusCode::EvmcRevert => Err(revert_error(data)),
+				EVMCStatusCode::EvmcOutOfGas => Err(internal_err("out of gas")),
 				_ => {
 					Err(Error {
 						code: ErrorCode::InternalError,
@@ -221,6 +266,32 @@ pub fn error_on_execution_failure(reason: &ExtendExitReason, data: &[u8]) -> Res
 	}
 }
 
+fn revert_error(data: &[u8]) -> Error {
+	let mut message = "VM Exception while processing transaction: revert".to_string();
+	if let Some(reason) = revert_reason(data) {
+		message = format!("{} {}", message, reason);
+	}
+	Error {
+		code: ErrorCode::InternalError,
+		message,
+		data: Some(Value::String(data.to_hex()))
+	}
+}
+
+/// Decode the reason of a revert raised with `Error(string)`.
+fn revert_reason(data: &[u8]) -> Option<&str> {
+	// Function selector (4) + offset (32) + string length (32), followed by the string.
+	const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+	if data.len() < 68 || data[0..4] != ERROR_SELECTOR {
+		return None;
+	}
+	let message_len = U256::from_big_endian(&data[36..68]);
+	if message_len > U256::from(data.len() - 68) {
+		return None;
+	}
+	std::str::from_utf8(&data[68..68 + message_len.as_usize()]).ok()
+}
+
 pub fn public_key(transaction: &EthereumTransaction) -> Result<
 	[u8; 64], sp_io::EcdsaVerifyError
 > {
@@ -322,3 +393,183 @@ impl EthSigner for EthDevSigner {
 		transaction.ok_or(internal_err("signer not available"))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Arc;
+	use ethereum_types::H256;
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_blockchain::HeaderBackend;
+	use sp_consensus::BlockOrigin;
+	use sp_runtime::{generic::BlockId, traits::Header as HeaderT};
+	use substrate_test_runtime_client::{
+		prelude::*, runtime::Block, DefaultTestClientBuilderExt, TestClient, TestClientBuilder,
+	};
+
+	use super::frontier_backend_client::{load_transactions, read_proof};
+
+	fn open_frontier_backend() -> Arc<fc_db::Backend<Block>> {
+		Arc::new(fc_db::Backend::<Block>::new(&fc_db::DatabaseSettings {
+			source: fc_db::DatabaseSettingsSrc::InMemory,
+		}).expect("open the frontier database"))
+	}
+
+	/// Import a block on top of `parent`, the storage change making it distinct from its siblings.
+	fn import_block(client: &mut Arc<TestClient>, parent: H256, fork: u8) -> H256 {
+		let mut builder = client.new_block_at(&BlockId::Hash(parent), Default::default(), false)
+			.expect("create a block builder");
+		builder.push_storage_change(vec![fork], None).expect("push a storage change");
+		let block = builder.build().expect("build the block").block;
+		let hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).expect("import the block");
+		hash
+	}
+
+	fn write_hashes(
+		backend: &fc_db::Backend<Block>,
+		block_hash: H256,
+		ethereum_block_hash: H256,
+		ethereum_transaction_hashes: Vec<H256>,
+	) {
+		backend.mapping().write_hashes(fc_db::MappingCommitment {
+			block_hash,
+			ethereum_block_hash,
+			ethereum_transaction_hashes,
+		}).expect("write the mapping");
+	}
+
+	#[test]
+	fn load_transactions_follows_the_canonical_chain() {
+		let frontier_backend = open_frontier_backend();
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+
+		// G -> A1 and G -> B1, A1 is imported first and stays the best block.
+		let a1_hash = import_block(&mut client, genesis_hash, 1);
+		let b1_hash = import_block(&mut client, genesis_hash, 2);
+		assert_eq!(client.info().best_hash, a1_hash);
+
+		let transaction_hash = H256::repeat_byte(0x11);
+		let ethereum_a1_hash = H256::repeat_byte(0xa1);
+		let ethereum_b1_hash = H256::repeat_byte(0xb1);
+		write_hashes(&frontier_backend, a1_hash, ethereum_a1_hash, vec![transaction_hash]);
+		write_hashes(&frontier_backend, b1_hash, ethereum_b1_hash, vec![H256::zero(), transaction_hash]);
+
+		assert_eq!(
+			load_transactions(client.as_ref(), &frontier_backend, transaction_hash).unwrap(),
+			Some((ethereum_a1_hash, 0)),
+		);
+
+		// G -> B1 -> B2 reorgs A1 out.
+		import_block(&mut client, b1_hash, 3);
+
+		assert_eq!(
+			load_transactions(client.as_ref(), &frontier_backend, transaction_hash).unwrap(),
+			Some((ethereum_b1_hash, 1)),
+		);
+	}
+
+	#[test]
+	fn load_transactions_ignores_retracted_blocks() {
+		let frontier_backend = open_frontier_backend();
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+
+		// The transaction is only included in A1, which G -> B1 -> B2 reorgs out.
+		let a1_hash = import_block(&mut client, genesis_hash, 1);
+		let transaction_hash = H256::repeat_byte(0x11);
+		write_hashes(&frontier_backend, a1_hash, H256::repeat_byte(0xa1), vec![transaction_hash]);
+
+		let b1_hash = import_block(&mut client, genesis_hash, 2);
+		import_block(&mut client, b1_hash, 3);
+
+		assert_eq!(
+			load_transactions(client.as_ref(), &frontier_backend, transaction_hash).unwrap(),
+			None,
+		);
+	}
+
+	#[test]
+	fn write_hashes_keeps_one_entry_per_block() {
+		let frontier_backend = open_frontier_backend();
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+
+		let a1_hash = import_block(&mut client, genesis_hash, 1);
+		let b1_hash = import_block(&mut client, genesis_hash, 2);
+
+		let transaction_hash = H256::repeat_byte(0x11);
+		write_hashes(&frontier_backend, a1_hash, H256::repeat_byte(0xa1), vec![transaction_hash]);
+		write_hashes(&frontier_backend, b1_hash, H256::repeat_byte(0xb1), vec![transaction_hash]);
+		// Syncing A1 again must not duplicate its candidate.
+		write_hashes(&frontier_backend, a1_hash, H256::repeat_byte(0xa1), vec![transaction_hash]);
+
+		let candidates: Vec<H256> = frontier_backend.mapping().transaction_metadata(&transaction_hash)
+			.unwrap()
+			.into_iter()
+			.map(|meta| meta.block_hash)
+			.collect();
+		assert_eq!(candidates.len(), 2);
+		assert!(candidates.contains(&a1_hash));
+		assert!(candidates.contains(&b1_hash));
+	}
+
+	#[test]
+	fn read_proof_verifies_against_the_state_root() {
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+
+		let mut builder = client.new_block_at(&BlockId::Hash(genesis_hash), Default::default(), false)
+			.expect("create a block builder");
+		builder.push_storage_change(b"proven".to_vec(), Some(vec![42])).expect("push a storage change");
+		let block = builder.build().expect("build the block").block;
+		let hash = block.header.hash();
+		let state_root = *block.header.state_root();
+		client.import(BlockOrigin::Own, block).expect("import the block");
+
+		let id = BlockId::Hash(hash);
+		let proof: Vec<Vec<u8>> = read_proof(client.as_ref(), &id, &[b"proven"]).unwrap()
+			.into_iter()
+			.map(|node| node.into_vec())
+			.collect();
+		assert_eq!(fp_rpc::verify_proof(state_root, proof, b"proven"), Ok(Some(vec![42])));
+
+		// A key that is not in the state is proven absent.
+		let proof: Vec<Vec<u8>> = read_proof(client.as_ref(), &id, &[b"missing"]).unwrap()
+			.into_iter()
+			.map(|node| node.into_vec())
+			.collect();
+		assert_eq!(fp_rpc::verify_proof(state_root, proof, b"missing"), Ok(None));
+	}
+
+	#[test]
+	fn tampered_read_proof_is_rejected() {
+		let mut client = Arc::new(TestClientBuilder::new().build());
+		let genesis_hash = client.info().genesis_hash;
+
+		let mut builder = client.new_block_at(&BlockId::Hash(genesis_hash), Default::default(), false)
+			.expect("create a block builder");
+		builder.push_storage_change(b"proven".to_vec(), Some(vec![42])).expect("push a storage change");
+		let block = builder.build().expect("build the block").block;
+		let hash = block.header.hash();
+		let state_root = *block.header.state_root();
+		client.import(BlockOrigin::Own, block).expect("import the block");
+
+		let mut proof: Vec<Vec<u8>> = read_proof(client.as_ref(), &BlockId::Hash(hash), &[b"proven"]).unwrap()
+			.into_iter()
+			.map(|node| node.into_vec())
+			.collect();
+		// Every node is on the path to the value, changing any of them breaks the proof.
+		let last = proof[0].len() - 1;
+		proof[0][last] ^= 0xff;
+		assert!(fp_rpc::verify_proof(state_root, proof, b"proven").is_err());
+
+		// Nor does an untampered proof verify against another state root.
+		let proof: Vec<Vec<u8>> = read_proof(client.as_ref(), &BlockId::Hash(hash), &[b"proven"]).unwrap()
+			.into_iter()
+			.map(|node| node.into_vec())
+			.collect();
+		let genesis_root = *client.header(BlockId::Hash(genesis_hash)).unwrap().unwrap().state_root();
+		assert!(fp_rpc::verify_proof(genesis_root, proof, b"proven").is_err());
+	}
+}
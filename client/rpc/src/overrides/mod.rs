@@ -18,16 +18,18 @@ use std::collections::BTreeMap;
 use ethereum::Block as EthereumBlock;
 use ethereum_types::{H160, H256, U256};
 use sp_runtime::traits::Block as BlockT;
-use sp_api::{BlockId, ProvideRuntimeApi};
+use sp_api::{ApiExt, BlockId, ProvideRuntimeApi};
 use sp_io::hashing::{twox_128, blake2_128};
-use fp_rpc::TransactionStatus;
+use fp_rpc::{TransactionStatus, EIP658Receipt};
 use std::{marker::PhantomData, sync::Arc};
 use fp_rpc::EthereumRuntimeRPCApi;
 
 mod schema_v1_override;
+mod schema_v2_override;
 
 pub use fc_rpc_core::{EthApiServer, NetApiServer};
 pub use schema_v1_override::SchemaV1Override;
+pub use schema_v2_override::SchemaV2Override;
 use pallet_ethereum::EthereumStorageSchema;
 
 pub struct OverrideHandle<Block: BlockT> {
@@ -48,7 +50,7 @@ pub trait StorageOverride<Block: BlockT> {
 	/// Return the current block.
 	fn current_block(&self, block: &BlockId<Block>) -> Option<EthereumBlock>;
 	/// Return the current receipt.
-	fn current_receipts(&self, block: &BlockId<Block>) -> Option<Vec<ethereum::Receipt>>;
+	fn current_receipts(&self, block: &BlockId<Block>) -> Option<Vec<EIP658Receipt>>;
 	/// Return the current transaction status.
 	fn current_transaction_statuses(&self, block: &BlockId<Block>) -> Option<Vec<TransactionStatus>>;
 }
@@ -63,6 +65,21 @@ fn blake2_128_extend(bytes: &[u8]) -> Vec<u8> {
 	ext
 }
 
+/// Convert receipts stored before schema V2, which carry the status in `state_root` and the gas
+/// used by each transaction alone, into EIP-658 receipts.
+fn legacy_receipts_convert(receipts: Vec<ethereum::Receipt>) -> Vec<EIP658Receipt> {
+	let mut cumulative_gas_used = U256::zero();
+	receipts.into_iter().map(|receipt| {
+		cumulative_gas_used = cumulative_gas_used.saturating_add(receipt.used_gas);
+		EIP658Receipt {
+			status_code: receipt.state_root.to_low_u64_be() as u8,
+			cumulative_gas_used,
+			logs_bloom: receipt.logs_bloom,
+			logs: receipt.logs,
+		}
+	}).collect()
+}
+
 /// A wrapper type for the Runtime API. This type implements `StorageOverride`, so it can be used
 /// when calling the runtime API is desired but a `dyn StorageOverride` is required.
 pub struct RuntimeApiStorageOverride<B: BlockT, C> {
@@ -113,8 +130,20 @@ where
 	}
 
 	/// Return the current receipt.
-	fn current_receipts(&self, block: &BlockId<Block>) -> Option<Vec<ethereum::Receipt>> {
-		self.client.runtime_api().current_receipts(&block).ok()?
+	fn current_receipts(&self, block: &BlockId<Block>) -> Option<Vec<EIP658Receipt>> {
+		let api = self.client.runtime_api();
+		let has_eip658_receipts = api.has_api_with::<dyn EthereumRuntimeRPCApi<Block>, _>(
+			&block,
+			|version| version >= 4,
+		).ok()?;
+
+		if has_eip658_receipts {
+			api.current_receipts(&block).ok()?
+		} else {
+			#[allow(deprecated)]
+			let receipts = api.current_receipts_before_version_4(&block).ok()??;
+			Some(legacy_receipts_convert(receipts))
+		}
 	}
 
 	/// Return the current transaction status.
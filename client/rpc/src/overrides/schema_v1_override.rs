@@ -23,9 +23,9 @@ use sp_storage::StorageKey;
 use codec::Decode;
 use sp_runtime::traits::{Block as BlockT, BlakeTwo256};
 use sp_api::BlockId;
-use fp_rpc::TransactionStatus;
+use fp_rpc::{TransactionStatus, EIP658Receipt};
 
-use super::{StorageOverride, storage_prefix_build, blake2_128_extend};
+use super::{StorageOverride, storage_prefix_build, blake2_128_extend, legacy_receipts_convert};
 
 /// An override for runtimes that use Schema V1
 pub struct SchemaV1Override<B: BlockT, C, BE> {
@@ -55,7 +55,7 @@ impl<B, C, BE> SchemaV1Override<B, C, BE> where
 	// 	Decode::decode(&mut &raw_data.0[..]).map_err(|_| "Could not decode data".into())
 	// }
 
-	fn query_storage<T: Decode>(&self, id: &BlockId<B>, key: &StorageKey) -> Option<T> {
+	pub(super) fn query_storage<T: Decode>(&self, id: &BlockId<B>, key: &StorageKey) -> Option<T> {
 		if let Ok(Some(data)) = self.client.storage(
 			id,
 			key
@@ -115,13 +115,13 @@ where
 	}
 
 	/// Return the current receipt.
-	fn current_receipts(&self, block: &BlockId<Block>) -> Option<Vec<ethereum::Receipt>> {
+	fn current_receipts(&self, block: &BlockId<Block>) -> Option<Vec<EIP658Receipt>> {
 		self.query_storage::<Vec<ethereum::Receipt>>(
 			block,
 			&StorageKey(
 				storage_prefix_build(b"Ethereum", b"CurrentReceipts")
 			)
-		)
+		).map(legacy_receipts_convert)
 	}
 
 	/// Return the current transaction status.
@@ -0,0 +1,361 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc, time};
+use ethereum::Block as EthereumBlock;
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::Result;
+use sha3::{Digest, Keccak256};
+use sp_runtime::traits::{Block as BlockT, BlakeTwo256, NumberFor, One, UniqueSaturatedInto, Zero};
+use sp_api::{ProvideRuntimeApi, ApiExt, BlockId, Core, HeaderT};
+use sp_blockchain::HeaderBackend;
+use sc_client_api::backend::{StorageProvider, Backend, StateBackend, AuxStore};
+use sc_client_api::BlockBackend;
+use fc_rpc_core::TraceApi as TraceApiT;
+use fc_rpc_core::types::{
+	Action, BlockNumber, Bytes, CallAction, CallResult, CreateAction, CreateResult, LocalizedTrace,
+	SuicideAction, TraceFilter, TraceResult,
+};
+use fp_rpc::{EthereumRuntimeRPCApi, DebugRuntimeApi};
+use fp_vm::tracing::{CallFrame, CallType, TracerKind, TransactionTrace};
+use crate::{frontier_backend_client, internal_err};
+use crate::overrides::OverrideHandle;
+
+pub use fc_rpc_core::TraceApiServer;
+
+pub struct TraceApi<B: BlockT, C, BE> {
+	client: Arc<C>,
+	backend: Arc<fc_db::Backend<B>>,
+	overrides: Arc<OverrideHandle<B>>,
+	max_past_logs: u32,
+	_marker: PhantomData<BE>,
+}
+
+impl<B: BlockT, C, BE> TraceApi<B, C, BE> {
+	pub fn new(
+		client: Arc<C>,
+		backend: Arc<fc_db::Backend<B>>,
+		overrides: Arc<OverrideHandle<B>>,
+		max_past_logs: u32,
+	) -> Self {
+		Self {
+			client,
+			backend,
+			overrides,
+			max_past_logs,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C, BE> TraceApi<B, C, BE> where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + AuxStore,
+	C: HeaderBackend<B> + BlockBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B> + DebugRuntimeApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+{
+	/// Call traces of every Ethereum transaction of a block keyed by transaction hash, read
+	/// from the trace cache or produced by replaying the block, in which case the cache is
+	/// filled.
+	fn call_frames(&self, id: BlockId<B>) -> Result<BTreeMap<H256, CallFrame>> {
+		let header = self.client.header(id)
+			.map_err(|err| internal_err(format!("fetch header failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("header not found"))?;
+		let hash = header.hash();
+
+		if let Some(frames) = self.backend.traces().block_traces::<BTreeMap<H256, CallFrame>>(&hash)
+			.map_err(|err| internal_err(format!("fetch cached traces failed: {:?}", err)))?
+		{
+			return Ok(frames);
+		}
+
+		// The genesis block has no transactions to replay.
+		if header.number().is_zero() {
+			return Ok(BTreeMap::new());
+		}
+
+		let extrinsics = self.client.block_body(&id)
+			.map_err(|err| internal_err(format!("fetch block body failed: {:?}", err)))?
+			.ok_or_else(|| internal_err("block body not found"))?;
+
+		// Replay the block on top of its parent state.
+		let parent_id = BlockId::Hash(*header.parent_hash());
+		let api = self.client.runtime_api();
+		let supported = api.has_api_with::<dyn DebugRuntimeApi<B>, _>(&parent_id, |version| version >= 2)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		if !supported {
+			return Err(internal_err("runtime does not support block tracing"));
+		}
+
+		api.initialize_block(&parent_id, &header)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?;
+		let traces = api.trace_block(&parent_id, extrinsics, TracerKind::Call)
+			.map_err(|err| internal_err(format!("runtime error: {:?}", err)))?
+			.map_err(|err| internal_err(format!("trace failed: {:?}", err)))?;
+
+		let frames = traces.into_iter()
+			.map(|(transaction_hash, trace)| match trace {
+				Ok(TransactionTrace::CallTrace(frame)) => Ok((transaction_hash, frame)),
+				Ok(TransactionTrace::StructLogs { .. }) => Err(internal_err("unexpected trace kind")),
+				Err(err) => Err(internal_err(
+					format!("trace of transaction {:?} failed: {:?}", transaction_hash, err)
+				)),
+			})
+			.collect::<Result<BTreeMap<_, _>>>()?;
+
+		self.backend.traces().write_block_traces(&hash, &frames)
+			.map_err(|err| internal_err(format!("write cached traces failed: {:?}", err)))?;
+
+		Ok(frames)
+	}
+
+	/// Localized traces of a block, `None` if there is no Ethereum block at `id`.
+	fn block_traces_by_id(&self, id: BlockId<B>) -> Result<Option<Vec<LocalizedTrace>>> {
+		let schema = frontier_backend_client::onchain_storage_schema::<B, C, BE>(self.client.as_ref(), id);
+		let handler = self.overrides.schemas.get(&schema).unwrap_or(&self.overrides.fallback);
+		let block = match handler.current_block(&id) {
+			Some(block) => block,
+			None => return Ok(None),
+		};
+
+		let frames = self.call_frames(id)?;
+		localized_traces(&block, &frames).map(Some)
+	}
+}
+
+impl<B, C, BE> TraceApiT for TraceApi<B, C, BE> where
+	C: ProvideRuntimeApi<B> + StorageProvider<B, BE> + AuxStore,
+	C: HeaderBackend<B> + BlockBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B> + DebugRuntimeApi<B>,
+	BE: Backend<B> + 'static,
+	BE::State: StateBackend<BlakeTwo256>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+{
+	fn block_traces(&self, number: BlockNumber) -> Result<Option<Vec<LocalizedTrace>>> {
+		let id = match frontier_backend_client::native_block_id::<B, C>(self.client.as_ref(), self.backend.as_ref(), Some(number))? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+
+		self.block_traces_by_id(id)
+	}
+
+	fn transaction_traces(&self, hash: H256) -> Result<Option<Vec<LocalizedTrace>>> {
+		let (ethereum_block_hash, index) = match frontier_backend_client::load_transactions::<B, C>(
+			self.client.as_ref(),
+			self.backend.as_ref(),
+			hash,
+		)? {
+			Some((hash, index)) => (hash, index as usize),
+			None => return Ok(None),
+		};
+		let id = match frontier_backend_client::load_hash::<B>(self.backend.as_ref(), ethereum_block_hash)? {
+			Some(id) => id,
+			None => return Ok(None),
+		};
+
+		Ok(self.block_traces_by_id(id)?.map(|traces| {
+			traces.into_iter().filter(|trace| trace.transaction_position == index).collect()
+		}))
+	}
+
+	fn filter_traces(&self, filter: TraceFilter) -> Result<Vec<LocalizedTrace>> {
+		// Max request duration of 10 seconds.
+		let max_duration = time::Duration::from_secs(10);
+		let begin_request = time::Instant::now();
+
+		let best_number = self.client.info().best_number;
+		let to_number: NumberFor<B> = filter.to_block.clone()
+			.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
+			.map(|s| s.unique_saturated_into())
+			.unwrap_or(best_number)
+			.min(best_number);
+		let from_number: NumberFor<B> = filter.from_block.clone()
+			.and_then(|v| frontier_backend_client::range_block_number::<B, C>(self.client.as_ref(), &v))
+			.map(|s| s.unique_saturated_into())
+			.unwrap_or(best_number);
+
+		let mut skip = filter.after.unwrap_or(0);
+		let count = filter.count.unwrap_or(usize::MAX);
+
+		let mut ret = Vec::new();
+		let mut current_number = from_number;
+		while current_number <= to_number && ret.len() < count {
+			if let Some(traces) = self.block_traces_by_id(BlockId::Number(current_number))? {
+				for trace in traces.into_iter().filter(|trace| trace_matches(&filter, trace)) {
+					if skip > 0 {
+						skip -= 1;
+					} else if ret.len() < count {
+						ret.push(trace);
+					}
+				}
+			}
+			// Check for restrictions
+			if ret.len() as u32 > self.max_past_logs {
+				return Err(internal_err(
+					format!("query returned more than {} results", self.max_past_logs)
+				));
+			}
+			if begin_request.elapsed() > max_duration {
+				return Err(internal_err(
+					format!("query timeout of {} seconds exceeded", max_duration.as_secs())
+				));
+			}
+			current_number = current_number + One::one();
+		}
+
+		Ok(ret)
+	}
+}
+
+/// Flatten the call frames of a block into OpenEthereum style traces. Fails if a
+/// transaction of the block has no trace.
+fn localized_traces(block: &EthereumBlock, frames: &BTreeMap<H256, CallFrame>) -> Result<Vec<LocalizedTrace>> {
+	let block_hash = H256::from_slice(
+		Keccak256::digest(&rlp::encode(&block.header)).as_slice()
+	);
+	let block_number = block.header.number.as_u64();
+
+	let mut ret = Vec::new();
+	for (index, transaction) in block.transactions.iter().enumerate() {
+		let transaction_hash = H256::from_slice(
+			Keccak256::digest(&rlp::encode(transaction)).as_slice()
+		);
+		let frame = frames.get(&transaction_hash).ok_or_else(|| internal_err(
+			format!("no trace for transaction {:?}", transaction_hash)
+		))?;
+		let mut push = |frame: &CallFrame, trace_address: &[usize]| {
+			let (action, result, trace_type) = trace_action(frame);
+			let error = frame.error.as_ref().map(|error| trace_error(&String::from_utf8_lossy(error)));
+			ret.push(LocalizedTrace {
+				action,
+				result: if error.is_some() { None } else { result },
+				error,
+				subtraces: frame.calls.len(),
+				trace_address: trace_address.to_vec(),
+				trace_type: trace_type.to_string(),
+				block_hash,
+				block_number,
+				transaction_hash,
+				transaction_position: index,
+			});
+		};
+		flatten(frame, &mut Vec::new(), &mut push);
+	}
+	Ok(ret)
+}
+
+/// Visit `frame` and its subcalls depth first, along with their position in the call tree.
+fn flatten<F: FnMut(&CallFrame, &[usize])>(frame: &CallFrame, trace_address: &mut Vec<usize>, f: &mut F) {
+	f(frame, trace_address);
+	for (index, call) in frame.calls.iter().enumerate() {
+		trace_address.push(index);
+		flatten(call, trace_address, f);
+		trace_address.pop();
+	}
+}
+
+fn trace_action(frame: &CallFrame) -> (Action, Option<TraceResult>, &'static str) {
+	let call_type = match frame.call_type {
+		CallType::Call => "call",
+		CallType::CallCode => "callcode",
+		CallType::DelegateCall => "delegatecall",
+		CallType::StaticCall => "staticcall",
+		CallType::Create | CallType::Create2 => {
+			return (
+				Action::Create(CreateAction {
+					from: frame.from,
+					gas: U256::from(frame.gas),
+					init: Bytes(frame.input.clone()),
+					value: frame.value,
+				}),
+				Some(TraceResult::Create(CreateResult {
+					address: frame.to,
+					code: Bytes(frame.output.clone()),
+					gas_used: U256::from(frame.gas_used),
+				})),
+				"create",
+			);
+		},
+		CallType::SelfDestruct => {
+			return (
+				Action::Suicide(SuicideAction {
+					address: frame.from,
+					balance: frame.value,
+					refund_address: frame.to,
+				}),
+				None,
+				"suicide",
+			);
+		},
+	};
+
+	(
+		Action::Call(CallAction {
+			call_type: call_type.to_string(),
+			from: frame.from,
+			to: frame.to,
+			gas: U256::from(frame.gas),
+			input: Bytes(frame.input.clone()),
+			value: frame.value,
+		}),
+		Some(TraceResult::Call(CallResult {
+			gas_used: U256::from(frame.gas_used),
+			output: Bytes(frame.output.clone()),
+		})),
+		"call",
+	)
+}
+
+/// Translate the error of a call frame to the wording used by OpenEthereum.
+fn trace_error(error: &str) -> String {
+	match error {
+		"execution reverted" => "Reverted",
+		"out of gas" => "Out of gas",
+		"invalid jump destination" => "Bad jump destination",
+		"invalid opcode" => "Bad instruction",
+		"stack underflow" => "Stack underflow",
+		"stack limit reached" => "Out of stack",
+		"max call depth exceeded" => "Call depth limit exceeded",
+		"return data out of bounds" => "Out of bounds",
+		error => error,
+	}.to_string()
+}
+
+/// Senders and recipients must both match when both are given.
+fn trace_matches(filter: &TraceFilter, trace: &LocalizedTrace) -> bool {
+	let (from, to): (H160, Option<H160>) = match &trace.action {
+		Action::Call(call) => (call.from, Some(call.to)),
+		Action::Create(create) => (create.from, match &trace.result {
+			Some(TraceResult::Create(result)) => Some(result.address),
+			_ => None,
+		}),
+		Action::Suicide(suicide) => (suicide.address, Some(suicide.refund_address)),
+	};
+
+	let from_matches = filter.from_address.as_ref()
+		.map(|addresses| addresses.is_empty() || addresses.contains(&from))
+		.unwrap_or(true);
+	let to_matches = filter.to_address.as_ref()
+		.map(|addresses| addresses.is_empty() || to.map(|to| addresses.contains(&to)).unwrap_or(false))
+		.unwrap_or(true);
+
+	from_matches && to_matches
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use std::{collections::BTreeMap, marker::PhantomData, sync::Arc};
+use ethereum::{Transaction as EthereumTransaction, TransactionAction};
+use ethereum_types::{H160, H256, U256};
+use jsonrpc_core::Result;
+use sha3::{Digest, Keccak256};
+use sp_runtime::traits::Block as BlockT;
+use sp_api::{ProvideRuntimeApi, BlockId};
+use sp_blockchain::HeaderBackend;
+use sc_transaction_graph::{ChainApi, Pool};
+use fc_rpc_core::TxPoolApi as TxPoolApiT;
+use fc_rpc_core::types::{
+	Summary, Transaction, TransactionMap, TxPoolContent, TxPoolInspect, TxPoolResult, TxPoolStatus,
+};
+use fp_rpc::{EthereumRuntimeRPCApi, TxPoolRuntimeApi};
+use crate::{internal_err, public_key};
+use crate::eth::transaction_build;
+
+pub use fc_rpc_core::TxPoolApiServer;
+
+pub struct TxPoolApi<B: BlockT, C, A: ChainApi> {
+	client: Arc<C>,
+	graph: Arc<Pool<A>>,
+	_marker: PhantomData<B>,
+}
+
+impl<B: BlockT, C, A: ChainApi> TxPoolApi<B, C, A> {
+	pub fn new(client: Arc<C>, graph: Arc<Pool<A>>) -> Self {
+		Self {
+			client,
+			graph,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<B, C, A> TxPoolApi<B, C, A> where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B> + TxPoolRuntimeApi<B>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	A: ChainApi<Block=B> + 'static,
+{
+	/// Ethereum transactions of the pool by sender and nonce, split into those that
+	/// follow the account nonce without a gap and those that do not.
+	fn pool_transactions(&self) -> Result<TxPoolResult<BTreeMap<H160, BTreeMap<U256, EthereumTransaction>>>> {
+		let validated_pool = self.graph.validated_pool();
+		let extrinsics: Vec<B::Extrinsic> = validated_pool.ready()
+			.map(|tx| tx.data.clone())
+			.chain(validated_pool.futures().into_iter().map(|(_, xt)| xt))
+			.collect();
+
+		let id = BlockId::Hash(self.client.info().best_hash);
+		let api = self.client.runtime_api();
+		let transactions = api.extrinsic_filter(&id, extrinsics)
+			.map_err(|err| internal_err(format!("fetch runtime extrinsic filter failed: {:?}", err)))?;
+
+		let mut by_sender: BTreeMap<H160, BTreeMap<U256, EthereumTransaction>> = BTreeMap::new();
+		for transaction in transactions {
+			let from = match public_key(&transaction) {
+				Ok(pk) => H160::from(H256::from_slice(Keccak256::digest(&pk).as_slice())),
+				Err(_) => continue,
+			};
+			by_sender.entry(from).or_default().insert(transaction.nonce, transaction);
+		}
+
+		let mut ret = TxPoolResult::default();
+		for (from, transactions) in by_sender {
+			let mut expected_nonce = api.account_basic(&id, from)
+				.map_err(|err| internal_err(format!("fetch runtime account basic failed: {:?}", err)))?
+				.nonce;
+			for (nonce, transaction) in transactions {
+				// Stale, the pool prunes it on the next block import.
+				if nonce < expected_nonce {
+					continue;
+				}
+				// Transactions are visited by increasing nonce, so the first gap
+				// makes every following transaction of the sender queued.
+				let bucket = if nonce == expected_nonce {
+					expected_nonce = expected_nonce.saturating_add(U256::one());
+					&mut ret.pending
+				} else {
+					&mut ret.queued
+				};
+				bucket.entry(from).or_insert_with(BTreeMap::new).insert(nonce, transaction);
+			}
+		}
+
+		Ok(ret)
+	}
+
+	fn map_transactions<T, F>(
+		transactions: BTreeMap<H160, BTreeMap<U256, EthereumTransaction>>,
+		f: F,
+	) -> TransactionMap<T> where
+		F: Fn(EthereumTransaction) -> T,
+	{
+		transactions.into_iter().map(|(from, transactions)| {
+			(from, transactions.into_iter().map(|(nonce, transaction)| {
+				(nonce.to_string(), f(transaction))
+			}).collect())
+		}).collect()
+	}
+}
+
+impl<B, C, A> TxPoolApiT for TxPoolApi<B, C, A> where
+	C: ProvideRuntimeApi<B> + HeaderBackend<B> + Send + Sync + 'static,
+	C::Api: EthereumRuntimeRPCApi<B> + TxPoolRuntimeApi<B>,
+	B: BlockT<Hash=H256> + Send + Sync + 'static,
+	A: ChainApi<Block=B> + 'static,
+{
+	fn content(&self) -> Result<TxPoolContent> {
+		let transactions = self.pool_transactions()?;
+		let build = |transaction: EthereumTransaction| -> Transaction {
+			transaction_build(transaction, None, None)
+		};
+
+		Ok(TxPoolResult {
+			pending: Self::map_transactions(transactions.pending, build),
+			queued: Self::map_transactions(transactions.queued, build),
+		})
+	}
+
+	fn inspect(&self) -> Result<TxPoolInspect> {
+		let transactions = self.pool_transactions()?;
+		let summarize = |transaction: EthereumTransaction| Summary {
+			to: match transaction.action {
+				TransactionAction::Call(to) => Some(to),
+				TransactionAction::Create => None,
+			},
+			value: transaction.value,
+			gas: transaction.gas_limit,
+			gas_price: transaction.gas_price,
+		};
+
+		Ok(TxPoolResult {
+			pending: Self::map_transactions(transactions.pending, summarize),
+			queued: Self::map_transactions(transactions.queued, summarize),
+		})
+	}
+
+	fn status(&self) -> Result<TxPoolStatus> {
+		let transactions = self.pool_transactions()?;
+		let count = |transactions: &BTreeMap<H160, BTreeMap<U256, EthereumTransaction>>| {
+			U256::from(transactions.values().map(|transactions| transactions.len()).sum::<usize>())
+		};
+
+		Ok(TxPoolResult {
+			pending: count(&transactions.pending),
+			queued: count(&transactions.queued),
+		})
+	}
+}
@@ -47,7 +47,7 @@ use codec::{Encode, Decode};
 use fp_consensus::{FRONTIER_ENGINE_ID, PostLog, PreLog};
 use fp_storage::PALLET_ETHEREUM_SCHEMA;
 
-pub use fp_rpc::TransactionStatus;
+pub use fp_rpc::{TransactionStatus, EIP658Receipt};
 pub use ethereum::{Transaction, Log, Block, Receipt, TransactionAction, TransactionMessage};
 
 #[cfg(all(feature = "std", test))]
@@ -67,6 +67,8 @@ pub enum ReturnValue {
 pub enum EthereumStorageSchema {
 	Undefined,
 	V1,
+	/// Receipts are stored as `EIP658Receipt`.
+	V2,
 }
 
 impl Default for EthereumStorageSchema {
@@ -100,12 +102,12 @@ pub trait Config: frame_system::Config<Hash=H256> + pallet_balances::Config + pa
 decl_storage! {
 	trait Store for Module<T: Config> as Ethereum {
 		/// Current building block's transactions and receipts.
-		Pending: Vec<(ethereum::Transaction, TransactionStatus, ethereum::Receipt)>;
+		Pending get(fn pending): Vec<(ethereum::Transaction, TransactionStatus, EIP658Receipt)>;
 
 		/// The current Ethereum block.
 		CurrentBlock: Option<ethereum::Block>;
 		/// The current Ethereum receipts.
-		CurrentReceipts: Option<Vec<ethereum::Receipt>>;
+		CurrentReceipts: Option<Vec<EIP658Receipt>>;
 		/// The current transaction statuses.
 		CurrentTransactionStatuses: Option<Vec<TransactionStatus>>;
 	}
@@ -115,7 +117,7 @@ decl_storage! {
 			<Module<T>>::store_block(false, U256::zero());
 
 			// Initialize the storage schema at the well known key.
-			frame_support::storage::unhashed::put::<EthereumStorageSchema>(&PALLET_ETHEREUM_SCHEMA, &EthereumStorageSchema::V1);
+			frame_support::storage::unhashed::put::<EthereumStorageSchema>(&PALLET_ETHEREUM_SCHEMA, &EthereumStorageSchema::V2);
 		});
 	}
 }
@@ -164,6 +166,19 @@ decl_module! {
 			);
 		}
 
+		fn on_runtime_upgrade() -> Weight {
+			let schema = frame_support::storage::unhashed::get::<EthereumStorageSchema>(&PALLET_ETHEREUM_SCHEMA)
+				.unwrap_or_default();
+			if schema < EthereumStorageSchema::V2 {
+				// Receipts of the previous block are kept as they are, the node reads
+				// them with the schema of that block.
+				frame_support::storage::unhashed::put::<EthereumStorageSchema>(&PALLET_ETHEREUM_SCHEMA, &EthereumStorageSchema::V2);
+				T::DbWeight::get().reads_writes(1, 1)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
 		fn on_initialize(n: T::BlockNumber) -> Weight {
 			Pending::kill();
 
@@ -289,7 +304,7 @@ impl<T: Config> Module<T> {
 			difficulty: U256::zero(),
 			number: block_number,
 			gas_limit: T::BlockGasLimit::get(),
-			gas_used: receipts.clone().into_iter().fold(U256::zero(), |acc, r| acc + r.used_gas),
+			gas_used: receipts.last().map(|r| r.cumulative_gas_used).unwrap_or_default(),
 			timestamp: UniqueSaturatedInto::<u64>::unique_saturated_into(
 				pallet_timestamp::Module::<T>::get()
 			),
@@ -334,7 +349,8 @@ impl<T: Config> Module<T> {
 		let transaction_hash = H256::from_slice(
 			Keccak256::digest(&rlp::encode(&transaction)).as_slice()
 		);
-		let transaction_index = Pending::get().len() as u32;
+		let pending = Pending::get();
+		let transaction_index = pending.len() as u32;
 
 		let (to, contract_address, info) = Self::execute(
 			source,
@@ -386,20 +402,16 @@ impl<T: Config> Module<T> {
 			},
 		};
 
-		let receipt = ethereum::Receipt {
-			state_root: match reason {
-				ExtendExitReason::ExitReason(ExitReason::Succeed(_)) => H256::from_low_u64_be(1),
-				ExtendExitReason::ExitReason(ExitReason::Error(_)) => H256::from_low_u64_le(0),
-				ExtendExitReason::ExitReason(ExitReason::Revert(_)) => H256::from_low_u64_le(0),
-				ExtendExitReason::ExitReason(ExitReason::Fatal(_)) => H256::from_low_u64_le(0),
-				ExtendExitReason::EVMCStatusCode(s) => {
-					match s {
-						EVMCStatusCode::EvmcSuccess => H256::from_low_u64_be(1),
-						_ => H256::from_low_u64_le(0),
-					}
-				}
+		let cumulative_gas_used = pending.last()
+			.map(|(_, _, receipt)| receipt.cumulative_gas_used)
+			.unwrap_or_default();
+		let receipt = EIP658Receipt {
+			status_code: match reason {
+				ExtendExitReason::ExitReason(ExitReason::Succeed(_)) |
+				ExtendExitReason::EVMCStatusCode(EVMCStatusCode::EvmcSuccess) => 1,
+				_ => 0,
 			},
-			used_gas,
+			cumulative_gas_used: cumulative_gas_used.saturating_add(used_gas),
 			logs_bloom: status.clone().logs_bloom,
 			logs: status.clone().logs,
 		};
@@ -437,7 +449,7 @@ impl<T: Config> Module<T> {
 	}
 
 	/// Get receipts by number.
-	pub fn current_receipts() -> Option<Vec<ethereum::Receipt>> {
+	pub fn current_receipts() -> Option<Vec<EIP658Receipt>> {
 		CurrentReceipts::get()
 	}
 
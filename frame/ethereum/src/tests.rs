@@ -243,6 +243,34 @@ fn transaction_should_generate_correct_gas_used() {
 	});
 }
 
+#[test]
+fn receipts_should_accumulate_gas_used() {
+	let (pairs, mut ext) = new_test_ext(1);
+	let alice = &pairs[0];
+
+	ext.execute_with(|| {
+		assert_ok!(Ethereum::transact(
+			Origin::none(),
+			default_erc20_creation_transaction(alice),
+		));
+		let mut second = default_erc20_creation_unsigned_transaction();
+		second.nonce = U256::from(1);
+		assert_ok!(Ethereum::transact(
+			Origin::none(),
+			second.sign(&alice.private_key),
+		));
+
+		let receipts: Vec<EIP658Receipt> = Ethereum::pending().into_iter()
+			.map(|(_, _, receipt)| receipt)
+			.collect();
+		assert_eq!(receipts.len(), 2);
+		assert_eq!(receipts[0].status_code, 1);
+		assert_eq!(receipts[1].status_code, 1);
+		assert_eq!(receipts[0].cumulative_gas_used, U256::from(891328));
+		assert_eq!(receipts[1].cumulative_gas_used, U256::from(891328 * 2));
+	});
+}
+
 #[test]
 fn call_should_handle_errors() {
 	// 	pragma solidity ^0.6.6;
@@ -0,0 +1,261 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! ERC-20 precompile for the native currency.
+//!
+//! Exposes `pallet_vm::Config::Currency` to contracts through the standard ERC-20
+//! interface. Balances are resolved through `pallet_vm::Config::AddressMapping`,
+//! and allowances are kept in a dedicated storage map keyed by EVM addresses.
+//!
+//! The precompile acts on behalf of `context.caller`, so it must be called
+//! directly: calls through `DELEGATECALL` or `CALLCODE`, where the caller is the
+//! account that called the delegating contract, are rejected.
+//!
+//! Transfers and allowances are written to Substrate storage rather than to the
+//! executor state. `pallet_vm` runs every call frame, precompile ones included,
+//! in its own storage transaction, so these writes are rolled back along with any
+//! enclosing frame that reverts. The precompile must only be used with a
+//! `pallet_vm::Config::Runner` that does so, as `pallet_vm::runner::stack::Runner`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::{vec, vec::Vec};
+use core::marker::PhantomData;
+use fp_vm::{Precompile, ensure_gas, abi::{decode_address, decode_u256, encode_bool, encode_string, encode_u256}};
+use evm::{ExitSucceed, ExitError, Context, executor::PrecompileOutput, backend::Log};
+use frame_support::{
+	Blake2_128Concat,
+	storage::types::{StorageDoubleMap, ValueQuery},
+	traits::{Currency, ExistenceRequirement, Get, StorageInstance},
+};
+use pallet_vm::{AddressMapping, BalanceOf, u256_to_runtime};
+use sp_core::{H160, H256, U256};
+use sp_runtime::traits::UniqueSaturatedInto;
+
+/// `balanceOf(address)`
+pub const SELECTOR_BALANCE_OF: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+/// `transfer(address,uint256)`
+pub const SELECTOR_TRANSFER: [u8; 4] = [0xa9, 0x05, 0x9c, 0xbb];
+/// `transferFrom(address,address,uint256)`
+pub const SELECTOR_TRANSFER_FROM: [u8; 4] = [0x23, 0xb8, 0x72, 0xdd];
+/// `approve(address,uint256)`
+pub const SELECTOR_APPROVE: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// `allowance(address,address)`
+pub const SELECTOR_ALLOWANCE: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+/// `totalSupply()`
+pub const SELECTOR_TOTAL_SUPPLY: [u8; 4] = [0x18, 0x16, 0x0d, 0xdd];
+/// `name()`
+pub const SELECTOR_NAME: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+/// `symbol()`
+pub const SELECTOR_SYMBOL: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+/// `decimals()`
+pub const SELECTOR_DECIMALS: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// `keccak256("Transfer(address,address,uint256)")`
+pub const TRANSFER_TOPIC: [u8; 32] = [
+	0xdd, 0xf2, 0x52, 0xad, 0x1b, 0xe2, 0xc8, 0x9b, 0x69, 0xc2, 0xb0, 0x68, 0xfc, 0x37, 0x8d, 0xaa,
+	0x95, 0x2b, 0xa7, 0xf1, 0x63, 0xc4, 0xa1, 0x16, 0x28, 0xf5, 0x5a, 0x4d, 0xf5, 0x23, 0xb3, 0xef,
+];
+/// `keccak256("Approval(address,address,uint256)")`
+pub const APPROVAL_TOPIC: [u8; 32] = [
+	0x8c, 0x5b, 0xe1, 0xe5, 0xeb, 0xec, 0x7d, 0x5b, 0xd1, 0x4f, 0x71, 0x42, 0x7d, 0x1e, 0x84, 0xf3,
+	0xdd, 0x03, 0x14, 0xc0, 0xf7, 0xb2, 0x29, 0x1e, 0x5b, 0x20, 0x0a, 0xc8, 0xc7, 0xc3, 0xb9, 0x25,
+];
+
+/// Gas charged for returning constant metadata.
+const GAS_CONSTANT: u64 = 200;
+/// Gas charged for a single storage read, in line with `SLOAD`.
+const GAS_READ: u64 = 800;
+/// Gas charged for a single storage write, in line with a non-fresh `SSTORE`.
+const GAS_WRITE: u64 = 5000;
+/// Gas charged for a three-topic log carrying one word of data.
+const GAS_LOG: u64 = 375 + 3 * 375 + 8 * 32;
+
+/// Metadata reported by the `name`, `symbol` and `decimals` methods.
+pub trait Erc20Metadata {
+	fn name() -> &'static str;
+	fn symbol() -> &'static str;
+	fn decimals() -> u8;
+}
+
+pub struct AllowancesPrefix;
+
+impl StorageInstance for AllowancesPrefix {
+	const STORAGE_PREFIX: &'static str = "Allowances";
+
+	fn pallet_prefix() -> &'static str {
+		"Erc20Balances"
+	}
+}
+
+/// Allowances granted by an owner (first key) to a spender (second key).
+pub type Allowances = StorageDoubleMap<
+	AllowancesPrefix,
+	Blake2_128Concat,
+	H160,
+	Blake2_128Concat,
+	H160,
+	U256,
+	ValueQuery,
+>;
+
+/// The native currency ERC-20 precompile. `A` is the address it is registered at.
+pub struct Erc20Balances<T: pallet_vm::Config, M: Erc20Metadata, A: Get<H160>> {
+	_marker: PhantomData<(T, M, A)>,
+}
+
+impl<T, M, A> Precompile for Erc20Balances<T, M, A> where
+	T: pallet_vm::Config,
+	M: Erc20Metadata,
+	A: Get<H160>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> core::result::Result<PrecompileOutput, ExitError> {
+		if input.len() < 4 {
+			return Err(ExitError::Other("input too short".into()))
+		}
+		if context.address != A::get() {
+			return Err(ExitError::Other("cannot be called through DELEGATECALL or CALLCODE".into()))
+		}
+		if context.apparent_value != U256::zero() {
+			return Err(ExitError::Other("function is not payable".into()))
+		}
+
+		let mut selector = [0u8; 4];
+		selector.copy_from_slice(&input[0..4]);
+		let args = &input[4..];
+
+		let (cost, output, logs) = match selector {
+			SELECTOR_NAME => {
+				ensure_gas(target_gas, GAS_CONSTANT)?;
+				(GAS_CONSTANT, encode_string(M::name()), Vec::new())
+			},
+			SELECTOR_SYMBOL => {
+				ensure_gas(target_gas, GAS_CONSTANT)?;
+				(GAS_CONSTANT, encode_string(M::symbol()), Vec::new())
+			},
+			SELECTOR_DECIMALS => {
+				ensure_gas(target_gas, GAS_CONSTANT)?;
+				(GAS_CONSTANT, encode_u256(U256::from(M::decimals())), Vec::new())
+			},
+			SELECTOR_TOTAL_SUPPLY => {
+				ensure_gas(target_gas, GAS_READ)?;
+				let total = balance_to_u256::<T>(T::Currency::total_issuance());
+				(GAS_READ, encode_u256(total), Vec::new())
+			},
+			SELECTOR_BALANCE_OF => {
+				ensure_gas(target_gas, GAS_READ)?;
+				let owner = decode_address(args, 0)?;
+				let balance = T::Currency::free_balance(&T::AddressMapping::into_account_id(owner));
+				(GAS_READ, encode_u256(balance_to_u256::<T>(balance)), Vec::new())
+			},
+			SELECTOR_ALLOWANCE => {
+				ensure_gas(target_gas, GAS_READ)?;
+				let owner = decode_address(args, 0)?;
+				let spender = decode_address(args, 1)?;
+				(GAS_READ, encode_u256(Allowances::get(owner, spender)), Vec::new())
+			},
+			SELECTOR_APPROVE => {
+				let cost = GAS_WRITE + GAS_LOG;
+				ensure_gas(target_gas, cost)?;
+				let spender = decode_address(args, 0)?;
+				let amount = decode_u256(args, 1)?;
+
+				Allowances::insert(context.caller, spender, amount);
+
+				let log = build_log(context.address, APPROVAL_TOPIC, context.caller, spender, amount);
+				(cost, encode_bool(true), vec![log])
+			},
+			SELECTOR_TRANSFER => {
+				let cost = 2 * GAS_READ + 2 * GAS_WRITE + GAS_LOG;
+				ensure_gas(target_gas, cost)?;
+				let to = decode_address(args, 0)?;
+				let amount = decode_u256(args, 1)?;
+
+				transfer::<T>(context.caller, to, amount)?;
+
+				let log = build_log(context.address, TRANSFER_TOPIC, context.caller, to, amount);
+				(cost, encode_bool(true), vec![log])
+			},
+			SELECTOR_TRANSFER_FROM => {
+				let cost = 3 * GAS_READ + 3 * GAS_WRITE + GAS_LOG;
+				ensure_gas(target_gas, cost)?;
+				let from = decode_address(args, 0)?;
+				let to = decode_address(args, 1)?;
+				let amount = decode_u256(args, 2)?;
+
+				let allowance = Allowances::get(from, context.caller);
+				if allowance < amount {
+					return Err(ExitError::Other("insufficient allowance".into()))
+				}
+
+				transfer::<T>(from, to, amount)?;
+
+				// An allowance of `U256::max_value()` is treated as unlimited.
+				if allowance != U256::max_value() {
+					Allowances::insert(from, context.caller, allowance - amount);
+				}
+
+				let log = build_log(context.address, TRANSFER_TOPIC, from, to, amount);
+				(cost, encode_bool(true), vec![log])
+			},
+			_ => return Err(ExitError::Other("unknown selector".into())),
+		};
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			cost,
+			output,
+			logs,
+		})
+	}
+}
+
+fn transfer<T: pallet_vm::Config>(from: H160, to: H160, amount: U256) -> Result<(), ExitError> {
+	let amount = u256_to_runtime::<BalanceOf<T>>(amount)
+		.ok_or_else(|| ExitError::Other("amount overflow".into()))?;
+
+	T::Currency::transfer(
+		&T::AddressMapping::into_account_id(from),
+		&T::AddressMapping::into_account_id(to),
+		amount,
+		ExistenceRequirement::AllowDeath,
+	).map_err(|_| ExitError::Other("transfer failed".into()))
+}
+
+fn balance_to_u256<T: pallet_vm::Config>(balance: BalanceOf<T>) -> U256 {
+	U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(balance))
+}
+
+fn build_log(address: H160, topic: [u8; 32], from: H160, to: H160, amount: U256) -> Log {
+	Log {
+		address,
+		topics: vec![H256::from(topic), H256::from(from), H256::from(to)],
+		data: encode_u256(amount),
+	}
+}
+
+#[cfg(all(feature = "std", test))]
+mod tests;
+
+#[cfg(all(feature = "std", test))]
+mod mock;
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Test utilities
+
+use super::*;
+use std::collections::BTreeMap;
+use frame_support::{impl_outer_origin, parameter_types, traits::GenesisBuild};
+use pallet_vm::{FeeCalculator, EnsureAddressNever, EnsureAddressRoot, GenesisAccount};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	AccountId32,
+};
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+pub struct PalletInfo;
+
+impl frame_support::traits::PalletInfo for PalletInfo {
+	fn index<P: 'static>() -> Option<usize> {
+		return Some(0)
+	}
+
+	fn name<P: 'static>() -> Option<&'static str> {
+		return Some("TestName")
+	}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Call = ();
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1000;
+}
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+pub struct FixedGasPrice;
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> U256 {
+		0.into()
+	}
+}
+
+pub struct TestAddressMapping;
+impl AddressMapping<AccountId32> for TestAddressMapping {
+	fn into_account_id(address: H160) -> AccountId32 {
+		let mut data = [0u8; 32];
+		data[0..20].copy_from_slice(&address[..]);
+		AccountId32::from(data)
+	}
+}
+
+pub struct TestMetadata;
+impl Erc20Metadata for TestMetadata {
+	fn name() -> &'static str {
+		"Unit"
+	}
+
+	fn symbol() -> &'static str {
+		"UNIT"
+	}
+
+	fn decimals() -> u8 {
+		18
+	}
+}
+
+parameter_types! {
+	pub PrecompileAddress: H160 = H160::from_low_u64_be(1);
+}
+
+impl pallet_vm::Config for Test {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = ();
+	type CallOrigin = EnsureAddressRoot<Self::AccountId>;
+	type WithdrawOrigin = EnsureAddressNever<Self::AccountId>;
+	type AddressMapping = TestAddressMapping;
+	type Currency = Balances;
+	type Event = ();
+	type Precompiles = (Erc20Balances<Test, TestMetadata, PrecompileAddress>,);
+	type Runner = pallet_vm::runner::stack::Runner<Self>;
+	type ChainId = ();
+	type BlockGasLimit = ();
+	type OnChargeTransaction = ();
+}
+
+pub type System = frame_system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type Erc20 = Erc20Balances<Test, TestMetadata, PrecompileAddress>;
+
+pub fn alice() -> H160 {
+	H160::from_low_u64_be(0xa11ce)
+}
+
+pub fn bob() -> H160 {
+	H160::from_low_u64_be(0xb0b)
+}
+
+pub fn charlie() -> H160 {
+	H160::from_low_u64_be(0xc4a7)
+}
+
+/// Forwards its input to the precompile with `CALL`, then reverts.
+pub fn reverting_caller() -> H160 {
+	H160::from_low_u64_be(0x1001)
+}
+
+/// Forwards its input to the precompile with `DELEGATECALL`, and returns the success flag.
+pub fn delegating_caller() -> H160 {
+	H160::from_low_u64_be(0x1002)
+}
+
+pub const INITIAL_BALANCE: u64 = 1_000_000;
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	let account = |code: Vec<u8>| GenesisAccount {
+		nonce: U256::from(1),
+		balance: U256::from(INITIAL_BALANCE),
+		storage: Default::default(),
+		code,
+	};
+
+	let mut accounts = BTreeMap::new();
+	accounts.insert(alice(), account(Vec::new()));
+	accounts.insert(bob(), account(Vec::new()));
+	accounts.insert(reverting_caller(), account(vec![
+		0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, CALLDATASIZE)
+		0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x00, 0x60, 0x01, 0x5a, 0xf1, // CALL(GAS, 1, 0, 0, CALLDATASIZE, 0, 0)
+		0x50, // POP
+		0x60, 0x00, 0x60, 0x00, 0xfd, // REVERT(0, 0)
+	]));
+	accounts.insert(delegating_caller(), account(vec![
+		0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, CALLDATASIZE)
+		0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00, 0x60, 0x01, 0x5a, 0xf4, // DELEGATECALL(GAS, 1, 0, CALLDATASIZE, 0, 0)
+		0x60, 0x00, 0x52, // MSTORE(0, success)
+		0x60, 0x20, 0x60, 0x00, 0xf3, // RETURN(0, 32)
+	]));
+
+	pallet_balances::GenesisConfig::<Test>::default().assimilate_storage(&mut t).unwrap();
+	<pallet_vm::GenesisConfig as GenesisBuild<Test>>::assimilate_storage(
+		&pallet_vm::GenesisConfig { accounts, beneficiaries: Vec::new() },
+		&mut t,
+	).unwrap();
+	t.into()
+}
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+use super::*;
+use mock::*;
+use pallet_vm::{Runner, ExtendExitReason};
+
+fn balance(address: H160) -> U256 {
+	U256::from(Balances::free_balance(&TestAddressMapping::into_account_id(address)))
+}
+
+fn context(caller: H160) -> Context {
+	Context {
+		address: PrecompileAddress::get(),
+		caller,
+		apparent_value: U256::zero(),
+	}
+}
+
+fn call_data(selector: [u8; 4], words: &[[u8; 32]]) -> Vec<u8> {
+	let mut out = selector.to_vec();
+	for word in words {
+		out.extend_from_slice(word);
+	}
+	out
+}
+
+fn address_word(address: H160) -> [u8; 32] {
+	H256::from(address).to_fixed_bytes()
+}
+
+fn u256_word(value: u64) -> [u8; 32] {
+	let mut out = [0u8; 32];
+	U256::from(value).to_big_endian(&mut out);
+	out
+}
+
+fn call_contract(target: H160, input: Vec<u8>) -> Vec<u8> {
+	let info = <Test as pallet_vm::Config>::Runner::call(
+		alice(),
+		target,
+		input,
+		U256::zero(),
+		1_000_000,
+		None,
+		None,
+		<Test as pallet_vm::Config>::config(),
+	).expect("call is executed");
+
+	match info.exit_reason {
+		ExtendExitReason::ExitReason(evm::ExitReason::Succeed(_)) => info.value,
+		_ => Vec::new(),
+	}
+}
+
+#[test]
+fn selectors_match_signatures() {
+	fp_vm::assert_selectors!(
+		"balanceOf(address)" => SELECTOR_BALANCE_OF,
+		"transfer(address,uint256)" => SELECTOR_TRANSFER,
+		"transferFrom(address,address,uint256)" => SELECTOR_TRANSFER_FROM,
+		"approve(address,uint256)" => SELECTOR_APPROVE,
+		"allowance(address,address)" => SELECTOR_ALLOWANCE,
+		"totalSupply()" => SELECTOR_TOTAL_SUPPLY,
+		"name()" => SELECTOR_NAME,
+		"symbol()" => SELECTOR_SYMBOL,
+		"decimals()" => SELECTOR_DECIMALS,
+	);
+	assert_eq!(sp_io::hashing::keccak_256(b"Transfer(address,address,uint256)"), TRANSFER_TOPIC);
+	assert_eq!(sp_io::hashing::keccak_256(b"Approval(address,address,uint256)"), APPROVAL_TOPIC);
+}
+
+#[test]
+fn transfer_moves_balance_and_logs() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_TRANSFER, &[address_word(bob()), u256_word(1000)]);
+		let output = Erc20::execute(&input, None, &context(alice())).unwrap();
+
+		assert_eq!(output.output, encode_bool(true));
+		assert_eq!(balance(alice()), U256::from(INITIAL_BALANCE - 1000));
+		assert_eq!(balance(bob()), U256::from(INITIAL_BALANCE + 1000));
+		assert_eq!(output.logs, vec![
+			build_log(PrecompileAddress::get(), TRANSFER_TOPIC, alice(), bob(), U256::from(1000)),
+		]);
+	});
+}
+
+#[test]
+fn approve_sets_allowance_and_logs() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_APPROVE, &[address_word(bob()), u256_word(500)]);
+		let output = Erc20::execute(&input, None, &context(alice())).unwrap();
+
+		assert_eq!(output.output, encode_bool(true));
+		assert_eq!(Allowances::get(alice(), bob()), U256::from(500));
+		assert_eq!(output.logs, vec![
+			build_log(PrecompileAddress::get(), APPROVAL_TOPIC, alice(), bob(), U256::from(500)),
+		]);
+
+		let input = call_data(SELECTOR_ALLOWANCE, &[address_word(alice()), address_word(bob())]);
+		let output = Erc20::execute(&input, None, &context(charlie())).unwrap();
+		assert_eq!(output.output, encode_u256(U256::from(500)));
+	});
+}
+
+#[test]
+fn transfer_from_spends_allowance() {
+	new_test_ext().execute_with(|| {
+		Allowances::insert(alice(), bob(), U256::from(500));
+
+		let input = call_data(
+			SELECTOR_TRANSFER_FROM,
+			&[address_word(alice()), address_word(charlie()), u256_word(200)],
+		);
+		let output = Erc20::execute(&input, None, &context(bob())).unwrap();
+
+		assert_eq!(output.output, encode_bool(true));
+		assert_eq!(balance(alice()), U256::from(INITIAL_BALANCE - 200));
+		assert_eq!(balance(charlie()), U256::from(200));
+		assert_eq!(Allowances::get(alice(), bob()), U256::from(300));
+		assert_eq!(output.logs, vec![
+			build_log(PrecompileAddress::get(), TRANSFER_TOPIC, alice(), charlie(), U256::from(200)),
+		]);
+	});
+}
+
+#[test]
+fn transfer_from_over_allowance_fails() {
+	new_test_ext().execute_with(|| {
+		Allowances::insert(alice(), bob(), U256::from(100));
+
+		let input = call_data(
+			SELECTOR_TRANSFER_FROM,
+			&[address_word(alice()), address_word(charlie()), u256_word(101)],
+		);
+		assert!(Erc20::execute(&input, None, &context(bob())).is_err());
+
+		assert_eq!(balance(alice()), U256::from(INITIAL_BALANCE));
+		assert_eq!(balance(charlie()), U256::zero());
+		assert_eq!(Allowances::get(alice(), bob()), U256::from(100));
+	});
+}
+
+#[test]
+fn delegatecall_is_rejected() {
+	new_test_ext().execute_with(|| {
+		// The precompile runs with the storage context of the delegating contract.
+		let input = call_data(SELECTOR_TRANSFER, &[address_word(charlie()), u256_word(1000)]);
+		let context = Context {
+			address: delegating_caller(),
+			caller: alice(),
+			apparent_value: U256::zero(),
+		};
+		assert!(Erc20::execute(&input, None, &context).is_err());
+
+		// Through a contract, the transfer would have been taken from its caller.
+		let output = call_contract(delegating_caller(), input);
+		assert_eq!(output, encode_bool(false));
+		assert_eq!(balance(alice()), U256::from(INITIAL_BALANCE));
+		assert_eq!(balance(charlie()), U256::zero());
+	});
+}
+
+#[test]
+fn transfer_is_rolled_back_with_the_reverting_frame() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_TRANSFER, &[address_word(charlie()), u256_word(1000)]);
+		call_contract(reverting_caller(), input);
+
+		assert_eq!(balance(reverting_caller()), U256::from(INITIAL_BALANCE));
+		assert_eq!(balance(charlie()), U256::zero());
+	});
+}
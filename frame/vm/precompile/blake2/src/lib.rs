@@ -114,11 +114,23 @@ impl Precompile for Blake2F {
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use pallet_vm_test_vector_support::test_precompile_test_vectors;
+	use pallet_vm_test_vector_support::{test_precompile_test_vectors, report_precompile_benchmark};
 
 	#[test]
 	fn process_consensus_tests() -> std::result::Result<(), String> {
 		test_precompile_test_vectors::<Blake2F>("../testdata/blake2F.json")?;
 		Ok(())
 	}
+
+	#[test]
+	fn process_consensus_failure_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Blake2F>("../testdata/fail-blake2f.json")?;
+		Ok(())
+	}
+
+	#[test]
+	#[ignore]
+	fn benchmark_consensus_tests() -> std::result::Result<(), String> {
+		report_precompile_benchmark::<Blake2F>("../testdata/blake2F.json", 10)
+	}
 }
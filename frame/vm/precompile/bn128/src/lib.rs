@@ -230,4 +230,22 @@ mod tests {
 		test_precompile_test_vectors::<Bn128Pairing>("../testdata/common_bnpair.json")?;
 		Ok(())
 	}
+
+	#[test]
+	fn process_consensus_failure_tests_for_add() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Bn128Add>("../testdata/fail-bnadd.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_consensus_failure_tests_for_mul() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Bn128Mul>("../testdata/fail-bnmul.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_consensus_failure_tests_for_pair() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Bn128Pairing>("../testdata/fail-bnpair.json")?;
+		Ok(())
+	}
 }
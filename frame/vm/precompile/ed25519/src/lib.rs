@@ -21,10 +21,26 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use core::convert::TryFrom;
-use fp_vm::LinearCostPrecompile;
-use evm::{ExitSucceed, ExitError};
+use fp_vm::{LinearCostPrecompile, Precompile};
+use evm::{ExitSucceed, ExitError, Context, executor::PrecompileOutput};
 use ed25519_dalek::{PublicKey, Verifier, Signature};
 
+/// Length of a single verification entry: message (32 bytes), public key (32 bytes)
+/// and signature (64 bytes).
+const ENTRY_LEN: usize = 128;
+
+/// Verify one entry, failing if the public key or signature cannot be decoded.
+fn verify_entry(entry: &[u8]) -> core::result::Result<bool, ExitError> {
+	let msg = &entry[0..32];
+	let pk = PublicKey::from_bytes(&entry[32..64])
+		.map_err(|_| ExitError::Other("Public key recover failed".into()))?;
+	let sig = Signature::try_from(&entry[64..128])
+		.map_err(|_| ExitError::Other("Signature recover failed".into()))?;
+
+	// https://docs.rs/rust-crypto/0.2.36/crypto/ed25519/fn.verify.html
+	Ok(pk.verify(msg, &sig).is_ok())
+}
+
 pub struct Ed25519Verify;
 
 impl LinearCostPrecompile for Ed25519Verify {
@@ -39,19 +55,9 @@ impl LinearCostPrecompile for Ed25519Verify {
 			return Err(ExitError::Other("input must contain 128 bytes".into()));
 		};
 
-		let mut i = [0u8; 128];
-		i[..128].copy_from_slice(&input[..128]);
-
 		let mut buf = [0u8; 4];
 
-		let msg = &i[0..32];
-		let pk = PublicKey::from_bytes(&i[32..64])
-			.map_err(|_| ExitError::Other("Public key recover failed".into()))?;
-		let sig = Signature::try_from(&i[64..128])
-			.map_err(|_| ExitError::Other("Signature recover failed".into()))?;
-
-		// https://docs.rs/rust-crypto/0.2.36/crypto/ed25519/fn.verify.html
-		if pk.verify(msg, &sig).is_ok() {
+		if verify_entry(&input[..ENTRY_LEN])? {
 			buf[3] = 0u8;
 		} else {
 			buf[3] = 1u8;
@@ -61,10 +67,75 @@ impl LinearCostPrecompile for Ed25519Verify {
 	}
 }
 
+/// Verifies a batch of ed25519 signatures in one call.
+///
+/// The input is a concatenation of 128 byte entries laid out as for `Ed25519Verify`.
+/// The output has the same format as well: the last byte is 0 if every signature
+/// is valid, and 1 otherwise.
+pub struct Ed25519BatchVerify;
+
+impl Ed25519BatchVerify {
+	const BASE: u64 = 60;
+	const PER_SIGNATURE: u64 = 3000;
+}
+
+impl Precompile for Ed25519BatchVerify {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<PrecompileOutput, ExitError> {
+		if input.is_empty() {
+			return Err(ExitError::Other("input must contain at least one signature".into()));
+		}
+		if input.len() % ENTRY_LEN != 0 {
+			return Err(ExitError::Other("input length must be a multiple of 128 bytes".into()));
+		}
+
+		let count = (input.len() / ENTRY_LEN) as u64;
+		let cost = Self::PER_SIGNATURE.checked_mul(count)
+			.and_then(|cost| cost.checked_add(Self::BASE))
+			.ok_or(ExitError::OutOfGas)?;
+		if let Some(gas_left) = target_gas {
+			if gas_left < cost {
+				return Err(ExitError::OutOfGas);
+			}
+		}
+
+		let mut all_valid = true;
+		for entry in input.chunks(ENTRY_LEN) {
+			all_valid &= verify_entry(entry)?;
+		}
+
+		let mut buf = [0u8; 4];
+		buf[3] = if all_valid { 0u8 } else { 1u8 };
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			cost,
+			output: buf.to_vec(),
+			logs: Default::default(),
+		})
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
 	use ed25519_dalek::{Keypair, SecretKey, Signer};
+	use pallet_vm_test_vector_support::test_precompile_test_vectors;
+
+	#[test]
+	fn process_batch_verify_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Ed25519BatchVerify>("../testdata/ed25519_batch.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_batch_verify_failure_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Ed25519BatchVerify>("../testdata/fail-ed25519_batch.json")?;
+		Ok(())
+	}
 
 	#[test]
 	fn test_empty_input() -> std::result::Result<(), ExitError> {
@@ -72,7 +143,7 @@ mod tests {
 		let input: [u8; 0] = [];
 		let cost: u64 = 1;
 
-		match Ed25519Verify::execute(&input, cost) {
+		match <Ed25519Verify as LinearCostPrecompile>::execute(&input, cost) {
 			Ok((_, _)) => {
 				panic!("Test not expected to pass");
 			},
@@ -114,7 +185,7 @@ mod tests {
 
 		let cost: u64 = 1;
 
-		match Ed25519Verify::execute(&input, cost) {
+		match <Ed25519Verify as LinearCostPrecompile>::execute(&input, cost) {
 			Ok((_, output)) => {
 				assert_eq!(output.len(), 4);
 				assert_eq!(output[0], 0u8);
@@ -136,7 +207,7 @@ mod tests {
 		input.extend_from_slice(&signature.to_bytes());
 		assert_eq!(input.len(), 128);
 
-		match Ed25519Verify::execute(&input, cost) {
+		match <Ed25519Verify as LinearCostPrecompile>::execute(&input, cost) {
 			Ok((_, output)) => {
 				assert_eq!(output.len(), 4);
 				assert_eq!(output[0], 0u8);
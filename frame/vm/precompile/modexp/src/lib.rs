@@ -20,61 +20,103 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
+use core::marker::PhantomData;
 use fp_vm::Precompile;
 use evm::{ExitSucceed, ExitError, Context, executor::PrecompileOutput};
 use num::{BigUint, Zero, One, ToPrimitive, FromPrimitive};
 
-use core::ops::BitAnd;
-use core::cmp::max;
-
-pub struct Modexp;
-
-const MIN_GAS_COST: u64 = 200;
+use core::cmp::{max, min};
+
+/// The modexp precompile, priced according to `P`.
+///
+/// Defaults to the Berlin (EIP-2565) pricing; use `Modexp<Eip198>` for pre-Berlin rules.
+pub struct Modexp<P: ModexpPricing = Eip2565>(PhantomData<P>);
+
+/// Gas pricing rules of the modexp precompile.
+pub trait ModexpPricing {
+	/// Gas cost of a call, given the lengths of its operands and the first (at most)
+	/// 32 bytes of the exponent.
+	fn calculate_gas_cost(
+		base_length: u64,
+		exp_length: u64,
+		mod_length: u64,
+		exponent_head: &BigUint,
+	) -> u64;
+}
 
-// Calculate gas cost according to EIP 2565:
-// https://eips.ethereum.org/EIPS/eip-2565
-fn calculate_gas_cost(
-	base_length: u64,
-	exp_length: u64,
-	mod_length: u64,
-	exponent: &BigUint,
-) -> u64 {
-
-	fn calculate_multiplication_complexity(base_length: u64, mod_length: u64) -> u64 {
-		let max_length = max(base_length, mod_length);
-		let mut words = max_length / 8;
-		if max_length % 8 > 0 {
-			words += 1;
+/// Original pricing, as introduced by EIP 198:
+/// https://eips.ethereum.org/EIPS/eip-198
+pub struct Eip198;
+
+const GQUADDIVISOR: u64 = 20;
+
+impl ModexpPricing for Eip198 {
+	fn calculate_gas_cost(
+		base_length: u64,
+		exp_length: u64,
+		mod_length: u64,
+		exponent_head: &BigUint,
+	) -> u64 {
+		fn calculate_multiplication_complexity(base_length: u64, mod_length: u64) -> u64 {
+			let x = max(base_length, mod_length);
+			if x <= 64 {
+				x * x
+			} else if x <= 1024 {
+				x * x / 4 + 96 * x - 3072
+			} else {
+				x * x / 16 + 480 * x - 199680
+			}
 		}
 
-		// TODO: prevent/handle overflow
-		words * words
+		let multiplication_complexity = calculate_multiplication_complexity(base_length, mod_length);
+		let iteration_count = calculate_iteration_count(exp_length, exponent_head);
+
+		multiplication_complexity.saturating_mul(iteration_count) / GQUADDIVISOR
 	}
+}
 
-	fn calculate_iteration_count(exp_length: u64, exponent: &BigUint) -> u64 {
-		let mut iteration_count: u64 = 0;
+/// Berlin pricing, as introduced by EIP 2565:
+/// https://eips.ethereum.org/EIPS/eip-2565
+pub struct Eip2565;
 
-		if exp_length <= 32 && exponent.is_zero() {
-			iteration_count = 0;
-		} else if exp_length <= 32 {
-			iteration_count = exponent.bits() - 1;
-		} else if exp_length > 32 {
+const MIN_GAS_COST: u64 = 200;
 
-			// construct BigUint to represent (2^256) - 1
-			let bytes: [u8; 32] = [0xFF; 32];
-			let max_256_bit_uint = BigUint::from_bytes_be(&bytes);
+impl ModexpPricing for Eip2565 {
+	fn calculate_gas_cost(
+		base_length: u64,
+		exp_length: u64,
+		mod_length: u64,
+		exponent_head: &BigUint,
+	) -> u64 {
+		fn calculate_multiplication_complexity(base_length: u64, mod_length: u64) -> u64 {
+			let max_length = max(base_length, mod_length);
+			let mut words = max_length / 8;
+			if max_length % 8 > 0 {
+				words += 1;
+			}
 
-			iteration_count = (8 * (exp_length - 32)) + ((exponent.bitand(max_256_bit_uint)).bits() - 1);
+			words.saturating_mul(words)
 		}
 
-		max(iteration_count, 1)
+		let multiplication_complexity = calculate_multiplication_complexity(base_length, mod_length);
+		let iteration_count = calculate_iteration_count(exp_length, exponent_head);
+
+		max(MIN_GAS_COST, multiplication_complexity.saturating_mul(iteration_count) / 3)
 	}
+}
 
-	let multiplication_complexity = calculate_multiplication_complexity(base_length, mod_length);
-	let iteration_count = calculate_iteration_count(exp_length, exponent);
-	let gas = max(MIN_GAS_COST, multiplication_complexity * iteration_count / 3);
+// The adjusted exponent length, shared by both pricing rules. Only the first 32 bytes
+// of the exponent are taken into account, longer exponents add 8 per extra byte.
+fn calculate_iteration_count(exp_length: u64, exponent_head: &BigUint) -> u64 {
+	let head_bits = exponent_head.bits().saturating_sub(1);
 
-	gas
+	let iteration_count = if exp_length <= 32 {
+		head_bits
+	} else {
+		(8 * (exp_length - 32)).saturating_add(head_bits)
+	};
+
+	max(iteration_count, 1)
 }
 
 // ModExp expects the following as inputs:
@@ -93,7 +135,7 @@ fn calculate_gas_cost(
 //
 //       see: https://eips.ethereum.org/EIPS/eip-198
 
-impl Precompile for Modexp {
+impl<P: ModexpPricing> Precompile for Modexp<P> {
 	fn execute(
 		input: &[u8],
 		target_gas: Option<u64>,
@@ -138,7 +180,14 @@ impl Precompile for Modexp {
 
 		// Gas formula allows arbitrary large exp_len when base and modulus are empty, so we need to handle empty base first.
 		let (r, gas_cost) = if base_len == 0 && mod_len == 0 {
-			(BigUint::zero(), MIN_GAS_COST)
+			let gas_cost = P::calculate_gas_cost(0, 0, 0, &BigUint::zero());
+			if let Some(gas_left) = target_gas {
+				if gas_left < gas_cost {
+					return Err(ExitError::OutOfGas);
+				}
+			};
+
+			(BigUint::zero(), gas_cost)
 		} else {
 
 			// read the numbers themselves.
@@ -147,10 +196,11 @@ impl Precompile for Modexp {
 
 			let exp_start = base_start + base_len;
 			let exponent = BigUint::from_bytes_be(&input[exp_start..exp_start + exp_len]);
+			let exponent_head = BigUint::from_bytes_be(&input[exp_start..exp_start + min(exp_len, 32)]);
 
 			// do our gas accounting
 			// TODO: we could technically avoid reading base first...
-			let gas_cost = calculate_gas_cost(base_len as u64, exp_len as u64, mod_len as u64, &exponent);
+			let gas_cost = P::calculate_gas_cost(base_len as u64, exp_len as u64, mod_len as u64, &exponent_head);
 			if let Some(gas_left) = target_gas {
 				if gas_left < gas_cost {
 					return Err(ExitError::OutOfGas);
@@ -203,10 +253,34 @@ mod tests {
 
 	#[test]
 	fn process_consensus_tests() -> std::result::Result<(), String> {
-		test_precompile_test_vectors::<Modexp>("../testdata/modexp_eip2565.json")?;
+		test_precompile_test_vectors::<Modexp<Eip2565>>("../testdata/modexp_eip2565.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_consensus_tests_eip198() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Modexp<Eip198>>("../testdata/modexp_eip198.json")?;
 		Ok(())
 	}
 
+	#[test]
+	fn process_consensus_failure_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Modexp<Eip2565>>("../testdata/fail-modexp.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn long_exponent_is_priced_on_its_head() {
+		// exponent is 2^256 spread over 33 bytes: the head is 0x01 followed by 31 zero bytes.
+		let mut head = [0u8; 32];
+		head[0] = 1;
+		let head = BigUint::from_bytes_be(&head);
+
+		assert_eq!(calculate_iteration_count(33, &head), 8 + 248);
+		assert_eq!(calculate_iteration_count(33, &BigUint::zero()), 8);
+		assert_eq!(calculate_iteration_count(0, &BigUint::zero()), 1);
+	}
+
 	#[test]
 	fn test_empty_input() -> std::result::Result<(), ExitError> {
 		let input: [u8; 0] = [];
@@ -219,7 +293,7 @@ mod tests {
 			apparent_value: From::from(0),
 		};
 
-		match Modexp::execute(&input, Some(cost), &context) {
+		match Modexp::<Eip2565>::execute(&input, Some(cost), &context) {
 			Ok(_) => {
 				panic!("Test not expected to pass");
 			},
@@ -246,7 +320,7 @@ mod tests {
 			apparent_value: From::from(0),
 		};
 
-		match Modexp::execute(&input, Some(cost), &context) {
+		match Modexp::<Eip2565>::execute(&input, Some(cost), &context) {
 			Ok(_) => {
 				panic!("Test not expected to pass");
 			},
@@ -274,7 +348,7 @@ mod tests {
 			apparent_value: From::from(0),
 		};
 
-		match Modexp::execute(&input, Some(cost), &context) {
+		match Modexp::<Eip2565>::execute(&input, Some(cost), &context) {
 			Ok(_) => {
 				panic!("Test not expected to pass");
 			},
@@ -306,7 +380,7 @@ mod tests {
 			apparent_value: From::from(0),
 		};
 
-		match Modexp::execute(&input, Some(cost), &context) {
+		match Modexp::<Eip2565>::execute(&input, Some(cost), &context) {
 			Ok(precompile_result) => {
 				assert_eq!(precompile_result.output.len(), 1); // should be same length as mod
 				let result = BigUint::from_bytes_be(&precompile_result.output[..]);
@@ -341,7 +415,7 @@ mod tests {
 			apparent_value: From::from(0),
 		};
 
-		match Modexp::execute(&input, Some(cost), &context) {
+		match Modexp::<Eip2565>::execute(&input, Some(cost), &context) {
 			Ok(precompile_result) => {
 				assert_eq!(precompile_result.output.len(), 32); // should be same length as mod
 				let result = BigUint::from_bytes_be(&precompile_result.output[..]);
@@ -373,7 +447,7 @@ mod tests {
 			apparent_value: From::from(0),
 		};
 
-		match Modexp::execute(&input, Some(cost), &context) {
+		match Modexp::<Eip2565>::execute(&input, Some(cost), &context) {
 			Ok(precompile_result) => {
 				assert_eq!(precompile_result.output.len(), 32); // should be same length as mod
 				let result = BigUint::from_bytes_be(&precompile_result.output[..]);
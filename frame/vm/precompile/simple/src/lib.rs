@@ -164,4 +164,22 @@ mod tests {
 		test_precompile_test_vectors::<Ripemd160>("../testdata/common_ripemd.json")?;
 		Ok(())
 	}
+
+	#[test]
+	fn process_consensus_failure_tests_for_ecrecover() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<ECRecover>("../testdata/fail-ecrecover.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_consensus_failure_tests_for_sha256() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Sha256>("../testdata/fail-sha256.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_consensus_failure_tests_for_ripemd160() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Ripemd160>("../testdata/fail-ripemd160.json")?;
+		Ok(())
+	}
 }
@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use fp_vm::{LinearCostPrecompile, Precompile};
+use evm::{ExitSucceed, ExitError, Context, executor::PrecompileOutput};
+use sp_core::sr25519::{Public, Signature};
+
+/// Length of a single verification entry: message (32 bytes), public key (32 bytes)
+/// and signature (64 bytes).
+const ENTRY_LEN: usize = 128;
+
+/// Verify one entry against the `substrate` signing context, as used for account signatures.
+fn verify_entry(entry: &[u8]) -> bool {
+	let mut pk = [0u8; 32];
+	pk.copy_from_slice(&entry[32..64]);
+	let mut sig = [0u8; 64];
+	sig.copy_from_slice(&entry[64..128]);
+
+	sp_io::crypto::sr25519_verify(&Signature::from_raw(sig), &entry[0..32], &Public::from_raw(pk))
+}
+
+/// Verifies a single sr25519 signature.
+///
+/// Input is message (32 bytes), public key (32 bytes) and signature (64 bytes). The
+/// output is 4 bytes, the last one being 0 for a valid signature and 1 otherwise.
+pub struct Sr25519Verify;
+
+impl LinearCostPrecompile for Sr25519Verify {
+	const BASE: u64 = 3000;
+	const WORD: u64 = 0;
+
+	fn execute(
+		input: &[u8],
+		_: u64,
+	) -> core::result::Result<(ExitSucceed, Vec<u8>), ExitError> {
+		if input.len() < ENTRY_LEN {
+			return Err(ExitError::Other("input must contain 128 bytes".into()));
+		};
+
+		let mut buf = [0u8; 4];
+		buf[3] = if verify_entry(&input[..ENTRY_LEN]) { 0u8 } else { 1u8 };
+
+		Ok((ExitSucceed::Returned, buf.to_vec()))
+	}
+}
+
+/// Verifies a batch of sr25519 signatures in one call.
+///
+/// The input is a concatenation of 128 byte entries laid out as for `Sr25519Verify`.
+/// The last byte of the output is 0 if every signature is valid, and 1 otherwise.
+pub struct Sr25519BatchVerify;
+
+impl Sr25519BatchVerify {
+	const BASE: u64 = 60;
+	const PER_SIGNATURE: u64 = 3000;
+}
+
+impl Precompile for Sr25519BatchVerify {
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		_context: &Context,
+	) -> core::result::Result<PrecompileOutput, ExitError> {
+		if input.is_empty() {
+			return Err(ExitError::Other("input must contain at least one signature".into()));
+		}
+		if input.len() % ENTRY_LEN != 0 {
+			return Err(ExitError::Other("input length must be a multiple of 128 bytes".into()));
+		}
+
+		let count = (input.len() / ENTRY_LEN) as u64;
+		let cost = Self::PER_SIGNATURE.checked_mul(count)
+			.and_then(|cost| cost.checked_add(Self::BASE))
+			.ok_or(ExitError::OutOfGas)?;
+		if let Some(gas_left) = target_gas {
+			if gas_left < cost {
+				return Err(ExitError::OutOfGas);
+			}
+		}
+
+		let all_valid = input.chunks(ENTRY_LEN).all(verify_entry);
+
+		let mut buf = [0u8; 4];
+		buf[3] = if all_valid { 0u8 } else { 1u8 };
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			cost,
+			output: buf.to_vec(),
+			logs: Default::default(),
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::{Pair, sr25519};
+	use pallet_vm_test_vector_support::test_precompile_test_vectors;
+
+	#[test]
+	fn process_verify_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Sr25519Verify>("../testdata/sr25519.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_batch_verify_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Sr25519BatchVerify>("../testdata/sr25519_batch.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_verify_failure_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Sr25519Verify>("../testdata/fail-sr25519.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn process_batch_verify_failure_tests() -> std::result::Result<(), String> {
+		test_precompile_test_vectors::<Sr25519BatchVerify>("../testdata/fail-sr25519_batch.json")?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_verify_account_signature() -> std::result::Result<(), ExitError> {
+		let pair = sr25519::Pair::from_seed(&[7u8; 32]);
+
+		let msg: &[u8] = b"abcdefghijklmnopqrstuvwxyz123456";
+		let signature = pair.sign(msg);
+
+		let mut input: Vec<u8> = Vec::with_capacity(128);
+		input.extend_from_slice(msg);
+		input.extend_from_slice(pair.public().as_ref());
+		input.extend_from_slice(signature.as_ref());
+
+		let (_, output) = <Sr25519Verify as LinearCostPrecompile>::execute(&input, 0)?;
+		assert_eq!(output, vec![0u8, 0, 0, 0]);
+
+		// same signature over a different message
+		input[0] = b'A';
+		let (_, output) = <Sr25519Verify as LinearCostPrecompile>::execute(&input, 0)?;
+		assert_eq!(output, vec![0u8, 0, 0, 1]);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validator set management precompile.
+//!
+//! Anyone can read the PoA authority set through `validators()` and
+//! `isValidator(address)`. The configured governance contract can additionally
+//! call `addValidator(bytes32)` and `removeValidator(bytes32)`, which are
+//! dispatched to `pallet_validator_set` with root origin and take effect at the
+//! next session rotation.
+//!
+//! Validators are substrate accounts, so they are exchanged as their raw 32-byte
+//! encoding. `isValidator` takes an EVM address and resolves it through
+//! `pallet_vm::Config::AddressMapping`.
+//!
+//! Adding an account that is already a validator, or removing one that is not,
+//! fails. As with the governance check, calls through `DELEGATECALL` or
+//! `CALLCODE` are rejected.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use fp_vm::{Precompile, ensure_gas, abi::{argument, decode_address, encode_bool, encode_bytes32_array}};
+use evm::{ExitSucceed, ExitError, Context, executor::PrecompileOutput};
+use frame_support::traits::Get;
+use frame_system::RawOrigin;
+use pallet_vm::AddressMapping;
+use codec::{Encode, Decode};
+use sp_core::{H160, U256};
+
+/// `validators()`
+pub const SELECTOR_VALIDATORS: [u8; 4] = [0xca, 0x1e, 0x78, 0x19];
+/// `isValidator(address)`
+pub const SELECTOR_IS_VALIDATOR: [u8; 4] = [0xfa, 0xcd, 0x74, 0x3b];
+/// `addValidator(bytes32)`
+pub const SELECTOR_ADD_VALIDATOR: [u8; 4] = [0x4e, 0xed, 0xc9, 0xb0];
+/// `removeValidator(bytes32)`
+pub const SELECTOR_REMOVE_VALIDATOR: [u8; 4] = [0xcd, 0x99, 0x3b, 0xa5];
+
+/// Gas charged for reading the validator set, in line with `SLOAD`.
+const GAS_READ: u64 = 800;
+/// Gas charged per validator returned by `validators()`.
+const GAS_PER_VALIDATOR: u64 = 3;
+/// Gas charged for updating the validator set and queueing a session rotation.
+const GAS_WRITE: u64 = 3 * 5000;
+
+/// The validator set precompile. `G` is the address of the governance contract
+/// allowed to modify the set, and `A` the address the precompile is registered at.
+pub struct ValidatorSet<T, G, A> {
+	_marker: PhantomData<(T, G, A)>,
+}
+
+impl<T, G, A> Precompile for ValidatorSet<T, G, A> where
+	T: pallet_vm::Config + pallet_validator_set::Config,
+	G: Get<H160>,
+	A: Get<H160>,
+{
+	fn execute(
+		input: &[u8],
+		target_gas: Option<u64>,
+		context: &Context,
+	) -> core::result::Result<PrecompileOutput, ExitError> {
+		if input.len() < 4 {
+			return Err(ExitError::Other("input too short".into()))
+		}
+		if context.address != A::get() {
+			return Err(ExitError::Other("cannot be called through DELEGATECALL or CALLCODE".into()))
+		}
+		if context.apparent_value != U256::zero() {
+			return Err(ExitError::Other("function is not payable".into()))
+		}
+
+		let mut selector = [0u8; 4];
+		selector.copy_from_slice(&input[0..4]);
+		let args = &input[4..];
+
+		let (cost, output) = match selector {
+			SELECTOR_VALIDATORS => {
+				let validators = pallet_validator_set::Module::<T>::validators().unwrap_or_default();
+				let cost = GAS_READ.saturating_add(GAS_PER_VALIDATOR.saturating_mul(validators.len() as u64));
+				ensure_gas(target_gas, cost)?;
+
+				let words = validators.iter()
+					.map(|v| encode_account(&v.encode()))
+					.collect::<Result<Vec<_>, _>>()?;
+				(cost, encode_bytes32_array(&words))
+			},
+			SELECTOR_IS_VALIDATOR => {
+				ensure_gas(target_gas, GAS_READ)?;
+				let address = decode_address(args, 0)?;
+				let account = T::AddressMapping::into_account_id(address);
+				(GAS_READ, encode_bool(is_validator::<T>(&account)))
+			},
+			SELECTOR_ADD_VALIDATOR | SELECTOR_REMOVE_VALIDATOR => {
+				ensure_gas(target_gas, GAS_WRITE)?;
+				if context.caller != G::get() {
+					return Err(ExitError::Other("caller is not the governance contract".into()))
+				}
+
+				let account = decode_account::<T>(args)?;
+				let is_validator = is_validator::<T>(&account);
+				if selector == SELECTOR_ADD_VALIDATOR && is_validator {
+					return Err(ExitError::Other("account is already a validator".into()))
+				}
+				if selector == SELECTOR_REMOVE_VALIDATOR && !is_validator {
+					return Err(ExitError::Other("account is not a validator".into()))
+				}
+
+				let result = if selector == SELECTOR_ADD_VALIDATOR {
+					pallet_validator_set::Module::<T>::add_validator(RawOrigin::<T::AccountId>::Root.into(), account)
+				} else {
+					pallet_validator_set::Module::<T>::remove_validator(RawOrigin::<T::AccountId>::Root.into(), account)
+				};
+				result.map_err(|_| ExitError::Other("dispatch execution failed".into()))?;
+
+				(GAS_WRITE, encode_bool(true))
+			},
+			_ => return Err(ExitError::Other("unknown selector".into())),
+		};
+
+		Ok(PrecompileOutput {
+			exit_status: ExitSucceed::Returned,
+			cost,
+			output,
+			logs: Default::default(),
+		})
+	}
+}
+
+fn is_validator<T: pallet_validator_set::Config>(account: &T::AccountId) -> bool {
+	pallet_validator_set::Module::<T>::validators()
+		.map(|validators| validators.contains(account))
+		.unwrap_or(false)
+}
+
+fn decode_account<T: frame_system::Config>(args: &[u8]) -> Result<T::AccountId, ExitError> {
+	let mut word = argument(args, 0)?;
+	T::AccountId::decode(&mut word).map_err(|_| ExitError::Other("invalid account".into()))
+}
+
+fn encode_account(encoded: &[u8]) -> Result<[u8; 32], ExitError> {
+	if encoded.len() > 32 {
+		return Err(ExitError::Other("account does not fit in bytes32".into()))
+	}
+
+	let mut word = [0u8; 32];
+	word[..encoded.len()].copy_from_slice(encoded);
+	Ok(word)
+}
+
+#[cfg(all(feature = "std", test))]
+mod tests;
+
+#[cfg(all(feature = "std", test))]
+mod mock;
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Test utilities
+//! Test utilities
+
+use super::*;
+use frame_support::{impl_outer_origin, parameter_types, traits::GenesisBuild};
+use pallet_vm::{FeeCalculator, EnsureAddressNever, EnsureAddressRoot};
+use sp_core::{H256, crypto::KeyTypeId};
+use sp_runtime::{
+	key_types::DUMMY,
+	testing::{Header, UintAuthorityId},
+	traits::{BlakeTwo256, IdentityLookup, OpaqueKeys},
+	AccountId32,
+};
+
+impl_outer_origin! {
+	pub enum Origin for Test where system = frame_system {}
+}
+
+pub struct PalletInfo;
+
+impl frame_support::traits::PalletInfo for PalletInfo {
+	fn index<P: 'static>() -> Option<usize> {
+		return Some(0)
+	}
+
+	fn name<P: 'static>() -> Option<&'static str> {
+		return Some("TestName")
+	}
+}
+
+#[derive(Clone, Eq, PartialEq)]
+pub struct Test;
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+impl frame_system::Config for Test {
+	type BaseCallFilter = ();
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Call = ();
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId32;
+	type Lookup = IdentityLookup<Self::AccountId>;
+	type Header = Header;
+	type Event = ();
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<u64>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const MinimumPeriod: u64 = 1000;
+}
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+pub struct TestSessionHandler;
+impl pallet_session::SessionHandler<AccountId32> for TestSessionHandler {
+	const KEY_TYPE_IDS: &'static [KeyTypeId] = &[DUMMY];
+
+	fn on_genesis_session<Ks: OpaqueKeys>(_validators: &[(AccountId32, Ks)]) {}
+
+	fn on_new_session<Ks: OpaqueKeys>(
+		_changed: bool,
+		_validators: &[(AccountId32, Ks)],
+		_queued_validators: &[(AccountId32, Ks)],
+	) {}
+
+	fn on_disabled(_validator_index: usize) {}
+}
+
+impl pallet_session::Config for Test {
+	type SessionHandler = TestSessionHandler;
+	type ShouldEndSession = ValidatorSetPallet;
+	type SessionManager = ValidatorSetPallet;
+	type Event = ();
+	type Keys = UintAuthorityId;
+	type NextSessionRotation = ValidatorSetPallet;
+	type ValidatorId = AccountId32;
+	type ValidatorIdOf = pallet_validator_set::ValidatorOf<Self>;
+	type DisabledValidatorsThreshold = ();
+	type WeightInfo = ();
+}
+
+impl pallet_validator_set::Config for Test {
+	type Event = ();
+}
+
+pub struct FixedGasPrice;
+impl FeeCalculator for FixedGasPrice {
+	fn min_gas_price() -> U256 {
+		0.into()
+	}
+}
+
+pub struct TestAddressMapping;
+impl AddressMapping<AccountId32> for TestAddressMapping {
+	fn into_account_id(address: H160) -> AccountId32 {
+		let mut data = [0u8; 32];
+		data[0..20].copy_from_slice(&address[..]);
+		AccountId32::from(data)
+	}
+}
+
+parameter_types! {
+	pub PrecompileAddress: H160 = H160::from_low_u64_be(1);
+	pub GovernanceAddress: H160 = H160::from_low_u64_be(0x60f);
+}
+
+impl pallet_vm::Config for Test {
+	type FeeCalculator = FixedGasPrice;
+	type GasWeightMapping = ();
+	type CallOrigin = EnsureAddressRoot<Self::AccountId>;
+	type WithdrawOrigin = EnsureAddressNever<Self::AccountId>;
+	type AddressMapping = TestAddressMapping;
+	type Currency = Balances;
+	type Event = ();
+	type Precompiles = (Validators,);
+	type Runner = pallet_vm::runner::stack::Runner<Self>;
+	type ChainId = ();
+	type BlockGasLimit = ();
+	type OnChargeTransaction = ();
+}
+
+pub type System = frame_system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type ValidatorSetPallet = pallet_validator_set::Module<Test>;
+pub type Validators = ValidatorSet<Test, GovernanceAddress, PrecompileAddress>;
+
+pub fn alice() -> H160 {
+	H160::from_low_u64_be(0xa11ce)
+}
+
+pub fn bob() -> H160 {
+	H160::from_low_u64_be(0xb0b)
+}
+
+pub fn account(address: H160) -> AccountId32 {
+	TestAddressMapping::into_account_id(address)
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	pallet_validator_set::GenesisConfig::<Test> {
+		validators: vec![account(alice())],
+	}.assimilate_storage(&mut t).unwrap();
+	t.into()
+}
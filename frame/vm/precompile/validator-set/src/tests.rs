@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+
+//! Test utilities
+use super::*;
+use mock::*;
+use sp_core::H256;
+
+fn context(caller: H160) -> Context {
+	Context {
+		address: PrecompileAddress::get(),
+		caller,
+		apparent_value: U256::zero(),
+	}
+}
+
+fn call_data(selector: [u8; 4], word: [u8; 32]) -> Vec<u8> {
+	let mut out = selector.to_vec();
+	out.extend_from_slice(&word);
+	out
+}
+
+fn account_word(address: H160) -> [u8; 32] {
+	encode_account(&account(address).encode()).unwrap()
+}
+
+#[test]
+fn selectors_match_signatures() {
+	fp_vm::assert_selectors!(
+		"validators()" => SELECTOR_VALIDATORS,
+		"isValidator(address)" => SELECTOR_IS_VALIDATOR,
+		"addValidator(bytes32)" => SELECTOR_ADD_VALIDATOR,
+		"removeValidator(bytes32)" => SELECTOR_REMOVE_VALIDATOR,
+	);
+}
+
+#[test]
+fn validators_returns_the_set() {
+	new_test_ext().execute_with(|| {
+		let output = Validators::execute(&SELECTOR_VALIDATORS, None, &context(bob())).unwrap();
+
+		assert_eq!(output.output, encode_bytes32_array(&[account_word(alice())]));
+		assert_eq!(output.cost, GAS_READ + GAS_PER_VALIDATOR);
+	});
+}
+
+#[test]
+fn is_validator_maps_the_address() {
+	new_test_ext().execute_with(|| {
+		let query = |address: H160| {
+			let input = call_data(SELECTOR_IS_VALIDATOR, H256::from(address).to_fixed_bytes());
+			Validators::execute(&input, None, &context(bob())).unwrap().output
+		};
+
+		assert_eq!(query(alice()), encode_bool(true));
+		assert_eq!(query(bob()), encode_bool(false));
+	});
+}
+
+#[test]
+fn governance_adds_and_removes_validators() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_ADD_VALIDATOR, account_word(bob()));
+		let output = Validators::execute(&input, None, &context(GovernanceAddress::get())).unwrap();
+
+		assert_eq!(output.output, encode_bool(true));
+		assert_eq!(ValidatorSetPallet::validators(), Some(vec![account(alice()), account(bob())]));
+
+		let input = call_data(SELECTOR_REMOVE_VALIDATOR, account_word(alice()));
+		Validators::execute(&input, None, &context(GovernanceAddress::get())).unwrap();
+
+		assert_eq!(ValidatorSetPallet::validators(), Some(vec![account(bob())]));
+	});
+}
+
+#[test]
+fn only_governance_modifies_the_set() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_ADD_VALIDATOR, account_word(bob()));
+
+		assert!(Validators::execute(&input, None, &context(alice())).is_err());
+		assert_eq!(ValidatorSetPallet::validators(), Some(vec![account(alice())]));
+	});
+}
+
+#[test]
+fn membership_is_checked_before_dispatch() {
+	new_test_ext().execute_with(|| {
+		let add = call_data(SELECTOR_ADD_VALIDATOR, account_word(alice()));
+		let remove = call_data(SELECTOR_REMOVE_VALIDATOR, account_word(bob()));
+
+		assert!(Validators::execute(&add, None, &context(GovernanceAddress::get())).is_err());
+		assert!(Validators::execute(&remove, None, &context(GovernanceAddress::get())).is_err());
+		assert_eq!(ValidatorSetPallet::validators(), Some(vec![account(alice())]));
+	});
+}
+
+#[test]
+fn delegatecall_from_governance_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_ADD_VALIDATOR, account_word(bob()));
+		let delegated = Context {
+			address: H160::from_low_u64_be(0x1002),
+			caller: GovernanceAddress::get(),
+			apparent_value: U256::zero(),
+		};
+
+		assert!(Validators::execute(&input, None, &delegated).is_err());
+		assert_eq!(ValidatorSetPallet::validators(), Some(vec![account(alice())]));
+	});
+}
+
+#[test]
+fn out_of_gas_is_reported() {
+	new_test_ext().execute_with(|| {
+		let input = call_data(SELECTOR_ADD_VALIDATOR, account_word(bob()));
+
+		assert_eq!(
+			Validators::execute(&input, Some(GAS_WRITE - 1), &context(GovernanceAddress::get())).err(),
+			Some(ExitError::OutOfGas),
+		);
+	});
+}
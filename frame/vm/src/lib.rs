@@ -61,11 +61,12 @@
 
 mod tests;
 pub mod runner;
+pub mod tracing;
 
 pub use crate::runner::Runner;
 pub use fp_vm::{
 	Account, Log, Vicinity, ExecutionInfo, CallInfo, CreateInfo, Precompile,
-	PrecompileSet, LinearCostPrecompile, ExtendExitReason, EVMCStatusCode
+	PrecompileSet, LinearCostPrecompile, ExtendExitReason, EVMCStatusCode, StateOverride,
 };
 pub use evm::{ExitReason, ExitSucceed, ExitError, ExitRevert, ExitFatal};
 
@@ -79,7 +80,7 @@ use frame_support::traits::{Currency, ExistenceRequirement, WithdrawReasons, Imb
 use frame_support::ensure;
 use frame_system::RawOrigin;
 use sp_core::{U256, H256, H160, Hasher};
-use sp_runtime::{AccountId32, traits::{UniqueSaturatedInto, BadOrigin, Saturating}};
+use sp_runtime::{AccountId32, traits::{UniqueSaturatedFrom, UniqueSaturatedInto, BadOrigin, Saturating}};
 use evm::Config as EvmConfig;
 
 pub use pallet::*;
@@ -412,6 +413,8 @@ pub mod pallet {
 		Forbidden,
 		/// Reward miner failed
 		RewardFailed,
+		/// State override value does not fit in the runtime type
+		StateOverrideOverflow,
 	}
 
 	#[pallet::genesis_config]
@@ -702,6 +705,49 @@ impl<T: Config> Pallet<T> {
 		<AccountCodes<T>>::insert(address, code);
 	}
 
+	/// Apply a state override to an account. Only meant for calls that are not
+	/// committed, such as `eth_call`. Fails without applying anything if the balance
+	/// or the nonce does not fit in the runtime type.
+	pub fn apply_state_override(address: &H160, state_override: &StateOverride) -> Result<(), Error<T>> {
+		let account_id = T::AddressMapping::into_account_id(*address);
+
+		let balance = state_override.balance
+			.map(|balance| u256_to_runtime::<BalanceOf<T>>(balance).ok_or(Error::<T>::StateOverrideOverflow))
+			.transpose()?;
+		let nonce = state_override.nonce
+			.map(|nonce| u256_to_runtime::<T::Index>(nonce).ok_or(Error::<T>::StateOverrideOverflow))
+			.transpose()?;
+
+		if let Some(balance) = balance {
+			T::Currency::make_free_balance_be(&account_id, balance);
+		}
+
+		if let Some(nonce) = nonce {
+			frame_system::Account::<T>::mutate(&account_id, |account| {
+				account.nonce = nonce;
+			});
+		}
+
+		if let Some(code) = &state_override.code {
+			<AccountCodes<T>>::insert(address, code);
+		}
+
+		if let Some(state) = &state_override.state {
+			<AccountStorages<T>>::remove_prefix(address);
+			for (index, value) in state {
+				<AccountStorages<T>>::insert(address, index, value);
+			}
+		}
+
+		if let Some(state_diff) = &state_override.state_diff {
+			for (index, value) in state_diff {
+				<AccountStorages<T>>::insert(address, index, value);
+			}
+		}
+
+		Ok(())
+	}
+
 	/// Get the account basic in EVM format.
 	pub fn account_basic(address: &H160) -> Account {
 		let account_id = T::AddressMapping::into_account_id(*address);
@@ -716,6 +762,21 @@ impl<T: Config> Pallet<T> {
 	}
 }
 
+/// Convert a `U256` to a runtime integer type, or `None` if it does not fit.
+pub fn u256_to_runtime<N: UniqueSaturatedFrom<u128> + UniqueSaturatedInto<u128> + Copy>(value: U256) -> Option<N> {
+	if value > U256::from(u128::max_value()) {
+		return None
+	}
+
+	let value = value.low_u128();
+	let converted = N::unique_saturated_from(value);
+	if UniqueSaturatedInto::<u128>::unique_saturated_into(converted) != value {
+		return None
+	}
+
+	Some(converted)
+}
+
 /// Handle withdrawing, refunding and depositing of transaction fees.
 /// Similar to `OnChargeTransaction` of `pallet_transaction_payment`
 pub trait OnChargeEVMTransaction<T: Config> {
@@ -38,6 +38,7 @@ use ssvm::host::HostContext as HostInterface;
 #[cfg(feature = "std")]
 use ssvm::types::*;
 use crate::runner::Runner as RunnerT;
+use crate::tracing::{self, VmEvent, CallType};
 
 #[derive(PartialEq)]
 pub enum ByteCodeKind {
@@ -164,7 +165,9 @@ impl<T: Config> Runner<T> {
 		let fee = T::OnChargeTransaction::withdraw_fee(&source, total_fee)?;
 
 		// Execute the EVM call
+		tracing::emit(VmEvent::EvmEnter);
 		let (reason, retv) = f(&mut executor);
+		tracing::emit(VmEvent::EvmExit);
 		let used_gas = U256::from(executor.used_gas());
 		let actual_fee = executor.fee(gas_price);
 		let mut state = executor.into_state();
@@ -250,6 +253,18 @@ impl<T: Config> Runner<T> {
 		let mut state = VmStackState::<T>::new(&vicinity, None, Some(tx_context));
 		state.inc_nonce(source);
 		state.substate.enter(gas_limit, is_static);
+		tracing::emit(VmEvent::SsvmEnter {
+			call_type: match call_kind {
+				CallKind::EVMC_CREATE => CallType::Create,
+				CallKind::EVMC_CREATE2 => CallType::Create2,
+				_ => CallType::Call,
+			},
+			from: source,
+			to: target,
+			value,
+			gas_limit,
+			input: &data,
+		});
 		let (output, gas_left, status_code) = {
 			let (is_precompiles, output, gas_left) = Self::execute_precompiles(&target, &data, &gas_limit);
 			if is_precompiles {
@@ -275,6 +290,11 @@ impl<T: Config> Runner<T> {
 			}
 		};
 		let used_gas = gas_limit as i64 - gas_left;
+		tracing::emit(VmEvent::SsvmExit {
+			reason: &ExtendExitReason::EVMCStatusCode(status_code.into()).into(),
+			output: &output,
+			gas_used: used_gas as u64,
+		});
 		let actual_fee = U256::from(used_gas) * gas_price;
 
 		// Refund fees to the `source` account if deducted more before,
@@ -327,3 +327,38 @@ fn set_eth_addr() {
 		assert_eq!(EVM::eth_addr(&sender), Some(evm_addr));
 	});
 }
+
+#[test]
+fn apply_state_override_sets_balance_and_nonce() {
+	new_test_ext().execute_with(|| {
+		let evm_addr = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let state_override = StateOverride {
+			balance: Some(U256::from(42)),
+			nonce: Some(U256::from(7)),
+			..Default::default()
+		};
+
+		assert_ok!(EVM::apply_state_override(&evm_addr, &state_override));
+		assert_eq!(EVM::account_basic(&evm_addr), Account { balance: U256::from(42), nonce: U256::from(7) });
+	});
+}
+
+#[test]
+fn apply_state_override_rejects_overflowing_values() {
+	new_test_ext().execute_with(|| {
+		let evm_addr = H160::from_str("1000000000000000000000000000000000000001").unwrap();
+		let before = EVM::account_basic(&evm_addr);
+
+		for state_override in vec![
+			StateOverride { balance: Some(U256::from(u128::max_value()) + 1), ..Default::default() },
+			StateOverride { balance: Some(U256::from(u64::max_value()) + 1), ..Default::default() },
+			StateOverride { balance: Some(U256::from(1)), nonce: Some(U256::max_value()), ..Default::default() },
+		] {
+			assert_err!(
+				EVM::apply_state_override(&evm_addr, &state_override).map_err(sp_runtime::DispatchError::from),
+				Error::<Test>::StateOverrideOverflow,
+			);
+			assert_eq!(EVM::account_basic(&evm_addr), before);
+		}
+	});
+}
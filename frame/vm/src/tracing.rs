@@ -0,0 +1,546 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Execution tracing.
+//!
+//! EVM execution is observed through the `tracing` events of the `evm` crates.
+//! Frames executed by SSVM do not emit those, so the runner reports them with
+//! [`VmEvent`] instead. Run the code to trace inside [`trace`] and turn the
+//! returned [`Tracer`] into a [`TransactionTrace`] with [`Tracer::finish`].
+//!
+//! The `evm` crates only emit events when the `tracing` feature is enabled, which
+//! slows down every execution. Without it [`ENABLED`] is false and traces would
+//! miss every EVM frame, so callers must refuse to trace.
+
+#![cfg_attr(not(feature = "tracing"), allow(dead_code))]
+
+use sp_std::{vec::Vec, rc::Rc, cell::RefCell, collections::btree_map::BTreeMap};
+use sp_core::{U256, H160, H256};
+use evm::{ExitReason, ExitError, ExitFatal};
+#[cfg(feature = "tracing")]
+use evm::{CreateScheme, tracing::{Event as EvmEvent, EventListener as EvmListener}};
+#[cfg(feature = "tracing")]
+use evm_runtime::tracing::{Event as RuntimeEvent, EventListener as RuntimeListener};
+#[cfg(feature = "tracing")]
+use evm_gasometer::{Snapshot, tracing::{Event as GasometerEvent, EventListener as GasometerListener}};
+pub use fp_vm::tracing::{
+	TracerKind, StructLogConfig, StructLog, CallType, FrameVm, CallFrame, TransactionTrace,
+};
+
+/// Whether EVM execution can be traced, that is whether the `tracing` feature is enabled.
+pub const ENABLED: bool = cfg!(feature = "tracing");
+
+/// Events reported by the runner itself.
+pub enum VmEvent<'a> {
+	/// An EVM executor is about to run a transaction.
+	EvmEnter,
+	/// The EVM executor finished.
+	EvmExit,
+	/// A frame is executed by SSVM.
+	SsvmEnter {
+		call_type: CallType,
+		from: H160,
+		to: H160,
+		value: U256,
+		gas_limit: u64,
+		input: &'a [u8],
+	},
+	/// The SSVM frame finished.
+	SsvmExit {
+		reason: &'a ExitReason,
+		output: &'a [u8],
+		gas_used: u64,
+	},
+}
+
+/// Listener for [`VmEvent`]s.
+pub trait EventListener {
+	fn event(&mut self, event: VmEvent);
+}
+
+environmental::environmental!(listener: dyn EventListener + 'static);
+
+/// Report an event to the active tracer, if any.
+pub fn emit(event: VmEvent) {
+	listener::with(|listener| listener.event(event));
+}
+
+/// Run `f` with a tracer of the given kind attached.
+pub fn trace<R, F: FnOnce() -> R>(kind: TracerKind, f: F) -> (R, Tracer) {
+	let tracer = Rc::new(RefCell::new(Tracer::new(kind)));
+	let mut vm_listener = SharedTracer(tracer.clone());
+
+	#[cfg(feature = "tracing")]
+	let result = {
+		let mut evm_listener = SharedTracer(tracer.clone());
+		let mut runtime_listener = SharedTracer(tracer.clone());
+		let mut gasometer_listener = SharedTracer(tracer.clone());
+
+		evm::tracing::using(&mut evm_listener, || {
+			evm_runtime::tracing::using(&mut runtime_listener, || {
+				evm_gasometer::tracing::using(&mut gasometer_listener, || {
+					listener::using(&mut vm_listener, f)
+				})
+			})
+		})
+	};
+	#[cfg(not(feature = "tracing"))]
+	let result = listener::using(&mut vm_listener, f);
+
+	let tracer = tracer.replace(Tracer::new(kind));
+	(result, tracer)
+}
+
+/// A frame that has been entered but has not exited yet.
+struct OpenFrame {
+	call: CallFrame,
+	/// Address whose storage the frame accesses.
+	address: H160,
+	/// Gas limit of the frame's gasometer, learnt from its first event.
+	gasometer_limit: Option<u64>,
+	gas_left: u64,
+	storage: BTreeMap<H256, H256>,
+}
+
+/// Collects the events of one execution.
+pub struct Tracer {
+	kind: TracerKind,
+	frames: Vec<OpenFrame>,
+	/// Number of open frames when each running EVM executor started. A single
+	/// executor may report the exit of its outermost frame twice, exits that
+	/// would close a frame of an outer executor are ignored.
+	executors: Vec<usize>,
+	root: Option<CallFrame>,
+	struct_logs: Vec<StructLog>,
+	/// Whether the last struct log is still being charged for.
+	step_open: bool,
+}
+
+impl Tracer {
+	fn new(kind: TracerKind) -> Self {
+		Self {
+			kind,
+			frames: Vec::new(),
+			executors: Vec::new(),
+			root: None,
+			struct_logs: Vec::new(),
+			step_open: false,
+		}
+	}
+
+	/// Build the trace. `gas_limit` and `used_gas` are those of the whole
+	/// transaction, which also cover the intrinsic cost that is not charged to
+	/// any frame. Returns `None` if nothing was executed.
+	pub fn finish(self, gas_limit: u64, used_gas: u64) -> Option<TransactionTrace> {
+		let mut root = self.root?;
+		root.gas = gas_limit;
+		root.gas_used = used_gas;
+
+		Some(match self.kind {
+			TracerKind::StructLog(_) => TransactionTrace::StructLogs {
+				gas: used_gas,
+				failed: root.error.is_some(),
+				return_value: root.output,
+				struct_logs: self.struct_logs,
+			},
+			TracerKind::Call => TransactionTrace::CallTrace(root),
+		})
+	}
+
+	fn struct_log_config(&self) -> Option<StructLogConfig> {
+		match self.kind {
+			TracerKind::StructLog(config) => Some(config),
+			TracerKind::Call => None,
+		}
+	}
+
+	fn enter(&mut self, call: CallFrame, address: H160) {
+		self.step_open = false;
+		self.frames.push(OpenFrame {
+			gasometer_limit: None,
+			gas_left: call.gas,
+			call,
+			address,
+			storage: BTreeMap::new(),
+		});
+	}
+
+	fn exit(&mut self, reason: &ExitReason, output: &[u8], gas_used: Option<u64>) {
+		self.step_open = false;
+		let frame = match self.frames.pop() {
+			Some(frame) => frame,
+			None => return,
+		};
+
+		let mut call = frame.call;
+		call.gas_used = gas_used.unwrap_or_else(|| match frame.gasometer_limit {
+			Some(limit) => limit.saturating_sub(frame.gas_left),
+			None => 0,
+		});
+		call.output = output.to_vec();
+		call.error = exit_error(reason);
+		self.close(call);
+	}
+
+	/// Attach a finished frame to its parent.
+	fn close(&mut self, call: CallFrame) {
+		match self.frames.last_mut() {
+			Some(parent) => parent.call.calls.push(call),
+			None => {
+				if self.root.is_none() {
+					self.root = Some(call);
+				}
+			},
+		}
+	}
+
+	fn executor_base(&self) -> usize {
+		self.executors.last().copied().unwrap_or(0)
+	}
+
+	#[cfg(feature = "tracing")]
+	fn evm_event(&mut self, event: EvmEvent) {
+		match event {
+			EvmEvent::Call { code_address, transfer, input, target_gas, is_static, context } => {
+				let call_type = if is_static {
+					CallType::StaticCall
+				} else if context.address != code_address {
+					if transfer.is_some() { CallType::CallCode } else { CallType::DelegateCall }
+				} else {
+					CallType::Call
+				};
+				let from = match self.frames.last() {
+					Some(parent) => parent.address,
+					None => context.caller,
+				};
+
+				self.enter(CallFrame {
+					call_type,
+					vm: FrameVm::Evm,
+					from,
+					to: code_address,
+					value: context.apparent_value,
+					gas: target_gas.unwrap_or_default(),
+					gas_used: 0,
+					input: input.to_vec(),
+					output: Vec::new(),
+					error: None,
+					calls: Vec::new(),
+				}, context.address);
+			},
+			EvmEvent::Create { caller, address, scheme, value, init_code, target_gas } => {
+				let call_type = match scheme {
+					CreateScheme::Create2 { .. } => CallType::Create2,
+					_ => CallType::Create,
+				};
+
+				self.enter(CallFrame {
+					call_type,
+					vm: FrameVm::Evm,
+					from: caller,
+					to: address,
+					value,
+					gas: target_gas.unwrap_or_default(),
+					gas_used: 0,
+					input: init_code.to_vec(),
+					output: Vec::new(),
+					error: None,
+					calls: Vec::new(),
+				}, address);
+			},
+			EvmEvent::Suicide { address, target, balance } => {
+				self.close(CallFrame {
+					call_type: CallType::SelfDestruct,
+					vm: FrameVm::Evm,
+					from: address,
+					to: target,
+					value: balance,
+					gas: 0,
+					gas_used: 0,
+					input: Vec::new(),
+					output: Vec::new(),
+					error: None,
+					calls: Vec::new(),
+				});
+			},
+			EvmEvent::Exit { reason, return_value } => {
+				if self.frames.len() > self.executor_base() {
+					self.exit(reason, return_value, None);
+				}
+			},
+			_ => (),
+		}
+	}
+
+	#[cfg(feature = "tracing")]
+	fn runtime_event(&mut self, event: RuntimeEvent) {
+		let config = match self.struct_log_config() {
+			Some(config) => config,
+			None => return,
+		};
+
+		match event {
+			RuntimeEvent::Step { opcode, position, stack, memory, .. } => {
+				let pc = match position {
+					Ok(pc) => *pc as u64,
+					Err(_) => return,
+				};
+				let gas = self.frames.last().map(|frame| frame.gas_left).unwrap_or_default();
+
+				self.struct_logs.push(StructLog {
+					pc,
+					op: opcode.0,
+					gas,
+					gas_cost: 0,
+					depth: self.frames.len() as u32,
+					stack: if config.disable_stack { None } else { Some(stack.data().clone()) },
+					memory: if config.disable_memory { None } else { Some(memory.data().clone()) },
+					storage: None,
+				});
+				self.step_open = true;
+			},
+			RuntimeEvent::StepResult { .. } => {
+				self.step_open = false;
+			},
+			RuntimeEvent::SLoad { index, value, .. } | RuntimeEvent::SStore { index, value, .. } => {
+				if config.disable_storage {
+					return
+				}
+
+				if let Some(frame) = self.frames.last_mut() {
+					frame.storage.insert(index, value);
+					if self.step_open {
+						if let Some(log) = self.struct_logs.last_mut() {
+							log.storage = Some(frame.storage.iter().map(|(k, v)| (*k, *v)).collect());
+						}
+					}
+				}
+			},
+		}
+	}
+
+	#[cfg(feature = "tracing")]
+	fn gasometer_event(&mut self, event: GasometerEvent) {
+		let snapshot = match event {
+			GasometerEvent::RecordCost { snapshot, .. } |
+			GasometerEvent::RecordRefund { snapshot, .. } |
+			GasometerEvent::RecordStipend { snapshot, .. } |
+			GasometerEvent::RecordDynamicCost { snapshot, .. } |
+			GasometerEvent::RecordTransaction { snapshot, .. } => snapshot,
+		};
+
+		let frame = match self.frames.last_mut() {
+			Some(frame) => frame,
+			None => return,
+		};
+		// Events of the parent's gasometer can still arrive while the frame is
+		// being exited.
+		match frame.gasometer_limit {
+			Some(limit) if limit != snapshot.gas_limit => return,
+			Some(_) => (),
+			None => frame.gasometer_limit = Some(snapshot.gas_limit),
+		}
+		frame.gas_left = gas_left(&snapshot);
+
+		if self.step_open {
+			if let Some(log) = self.struct_logs.last_mut() {
+				log.gas_cost = log.gas.saturating_sub(frame.gas_left);
+			}
+		}
+	}
+
+	fn vm_event(&mut self, event: VmEvent) {
+		match event {
+			VmEvent::EvmEnter => {
+				let open = self.frames.len();
+				self.executors.push(open);
+			},
+			VmEvent::EvmExit => {
+				let base = self.executors.pop().unwrap_or(0);
+				while self.frames.len() > base {
+					self.exit(&ExitReason::Fatal(ExitFatal::UnhandledInterrupt), &[], None);
+				}
+			},
+			VmEvent::SsvmEnter { call_type, from, to, value, gas_limit, input } => {
+				self.enter(CallFrame {
+					call_type,
+					vm: FrameVm::Ewasm,
+					from,
+					to,
+					value,
+					gas: gas_limit,
+					gas_used: 0,
+					input: input.to_vec(),
+					output: Vec::new(),
+					error: None,
+					calls: Vec::new(),
+				}, to);
+			},
+			VmEvent::SsvmExit { reason, output, gas_used } => {
+				self.exit(reason, output, Some(gas_used));
+			},
+		}
+	}
+}
+
+/// Forwards the events of every source to the same tracer.
+struct SharedTracer(Rc<RefCell<Tracer>>);
+
+#[cfg(feature = "tracing")]
+impl EvmListener for SharedTracer {
+	fn event(&mut self, event: EvmEvent) {
+		self.0.borrow_mut().evm_event(event);
+	}
+}
+
+#[cfg(feature = "tracing")]
+impl RuntimeListener for SharedTracer {
+	fn event(&mut self, event: RuntimeEvent) {
+		self.0.borrow_mut().runtime_event(event);
+	}
+}
+
+#[cfg(feature = "tracing")]
+impl GasometerListener for SharedTracer {
+	fn event(&mut self, event: GasometerEvent) {
+		self.0.borrow_mut().gasometer_event(event);
+	}
+}
+
+impl EventListener for SharedTracer {
+	fn event(&mut self, event: VmEvent) {
+		self.0.borrow_mut().vm_event(event);
+	}
+}
+
+#[cfg(feature = "tracing")]
+fn gas_left(snapshot: &Snapshot) -> u64 {
+	snapshot.gas_limit
+		.saturating_sub(snapshot.used_gas)
+		.saturating_sub(snapshot.memory_gas)
+}
+
+/// Error message of a frame, following the wording of geth where there is one.
+fn exit_error(reason: &ExitReason) -> Option<Vec<u8>> {
+	let message: &str = match reason {
+		ExitReason::Succeed(_) => return None,
+		ExitReason::Revert(_) => "execution reverted",
+		ExitReason::Error(error) => match error {
+			ExitError::StackUnderflow => "stack underflow",
+			ExitError::StackOverflow => "stack limit reached",
+			ExitError::InvalidJump => "invalid jump destination",
+			ExitError::InvalidRange => "return data out of bounds",
+			ExitError::DesignatedInvalid => "invalid opcode",
+			ExitError::CallTooDeep => "max call depth exceeded",
+			ExitError::CreateCollision => "contract address collision",
+			ExitError::CreateContractLimit => "max code size exceeded",
+			ExitError::OutOfOffset => "offset out of bounds",
+			ExitError::OutOfGas => "out of gas",
+			ExitError::OutOfFund => "insufficient balance for transfer",
+			ExitError::PCUnderflow => "pc underflow",
+			ExitError::CreateEmpty => "empty create",
+			ExitError::Other(message) => &**message,
+		},
+		ExitReason::Fatal(fatal) => match fatal {
+			ExitFatal::NotSupported => "not supported",
+			ExitFatal::UnhandledInterrupt => "unhandled interrupt",
+			ExitFatal::CallErrorAsFatal(_) => "call error as fatal",
+			ExitFatal::Other(message) => &**message,
+		},
+	};
+
+	Some(message.as_bytes().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nested_ssvm_frames_are_collected() {
+		let ((), tracer) = trace(TracerKind::Call, || {
+			emit(VmEvent::SsvmEnter {
+				call_type: CallType::Call,
+				from: H160::from_low_u64_be(1),
+				to: H160::from_low_u64_be(2),
+				value: U256::zero(),
+				gas_limit: 100,
+				input: &[1, 2],
+			});
+			emit(VmEvent::SsvmEnter {
+				call_type: CallType::Create,
+				from: H160::from_low_u64_be(2),
+				to: H160::from_low_u64_be(3),
+				value: U256::zero(),
+				gas_limit: 50,
+				input: &[],
+			});
+			emit(VmEvent::SsvmExit {
+				reason: &ExitReason::Error(ExitError::OutOfGas),
+				output: &[],
+				gas_used: 50,
+			});
+			emit(VmEvent::SsvmExit {
+				reason: &ExitReason::Succeed(evm::ExitSucceed::Returned),
+				output: &[3],
+				gas_used: 80,
+			});
+		});
+
+		let root = match tracer.finish(21_100, 21_080) {
+			Some(TransactionTrace::CallTrace(root)) => root,
+			_ => panic!("expected a call trace"),
+		};
+		assert_eq!(root.vm, FrameVm::Ewasm);
+		assert_eq!(root.gas_used, 21_080);
+		assert_eq!(root.input, vec![1, 2]);
+		assert_eq!(root.output, vec![3]);
+		assert_eq!(root.error, None);
+		assert_eq!(root.calls.len(), 1);
+		assert_eq!(root.calls[0].call_type, CallType::Create);
+		assert_eq!(root.calls[0].gas_used, 50);
+		assert_eq!(root.calls[0].error, Some(b"out of gas".to_vec()));
+	}
+
+	#[test]
+	fn frames_left_open_are_closed_when_the_executor_exits() {
+		let ((), tracer) = trace(TracerKind::Call, || {
+			emit(VmEvent::EvmEnter);
+			emit(VmEvent::SsvmEnter {
+				call_type: CallType::Call,
+				from: H160::from_low_u64_be(1),
+				to: H160::from_low_u64_be(2),
+				value: U256::zero(),
+				gas_limit: 100,
+				input: &[],
+			});
+			emit(VmEvent::EvmExit);
+		});
+
+		let root = match tracer.finish(100, 100) {
+			Some(TransactionTrace::CallTrace(root)) => root,
+			_ => panic!("expected a call trace"),
+		};
+		assert_eq!(root.error, Some(b"unhandled interrupt".to_vec()));
+	}
+
+	#[test]
+	fn nothing_executed_gives_no_trace() {
+		let ((), tracer) = trace(TracerKind::StructLog(Default::default()), || ());
+		assert!(tracer.finish(100, 0).is_none());
+	}
+}
@@ -16,61 +16,151 @@
 // limitations under the License.
 
 use fp_vm::Precompile;
-use evm::{ExitSucceed, Context, executor::PrecompileOutput};
+use evm::{ExitSucceed, ExitError, Context, executor::PrecompileOutput};
 
+#[cfg(feature = "std")]
+use primitive_types::{H160, U256};
 #[cfg(feature = "std")]
 use serde::Deserialize;
 
+/// Gas limit handed to the precompile when a vector does not specify its own `GasLimit`.
+pub const DEFAULT_GAS_LIMIT: u64 = 10_000_000;
+
 #[allow(non_snake_case)]
 #[derive(Deserialize, Debug)]
 #[cfg(feature = "std")]
 struct EthConsensusTest {
 	Input: String,
+	#[serde(default)]
 	Expected: String,
 	Name: String,
 	Gas: Option<u64>,
+	/// Set for failure vectors. Matched against the message of `ExitError::Other`, or
+	/// against the variant name (e.g. `OutOfGas`) for any other error.
+	ExpectedError: Option<String>,
+	/// Overrides `DEFAULT_GAS_LIMIT` for this vector.
+	GasLimit: Option<u64>,
+	/// Overrides the caller of the base context, as a hex encoded address.
+	Caller: Option<String>,
+	/// Overrides the address of the base context, as a hex encoded address.
+	Address: Option<String>,
+	/// Overrides the apparent value of the base context, as a hex encoded integer.
+	Value: Option<String>,
+	#[serde(default)]
+	NoBenchmark: bool,
+}
+
+#[cfg(feature = "std")]
+impl EthConsensusTest {
+	fn input(&self) -> Result<Vec<u8>, String> {
+		hex::decode(&self.Input)
+			.map_err(|e| format!("Test '{}' has invalid input: {:?}", self.Name, e))
+	}
+
+	fn context(&self, base: &Context) -> Result<Context, String> {
+		let address = |value: &str| -> Result<H160, String> {
+			let bytes = hex::decode(value.trim_start_matches("0x"))
+				.map_err(|e| format!("Test '{}' has invalid address: {:?}", self.Name, e))?;
+			if bytes.len() != 20 {
+				return Err(format!("Test '{}' has invalid address length: {}", self.Name, bytes.len()));
+			}
+			Ok(H160::from_slice(&bytes))
+		};
+
+		Ok(Context {
+			address: match &self.Address {
+				Some(value) => address(value)?,
+				None => base.address,
+			},
+			caller: match &self.Caller {
+				Some(value) => address(value)?,
+				None => base.caller,
+			},
+			apparent_value: match &self.Value {
+				Some(value) => U256::from_str_radix(value.trim_start_matches("0x"), 16)
+					.map_err(|e| format!("Test '{}' has invalid value: {:?}", self.Name, e))?,
+				None => base.apparent_value,
+			},
+		})
+	}
+}
+
+#[cfg(feature = "std")]
+fn read_test_vectors(filepath: &str) -> Result<Vec<EthConsensusTest>, String> {
+	let data = std::fs::read_to_string(filepath)
+		.map_err(|e| format!("Failed to read {}: {}", filepath, e))?;
+
+	serde_json::from_str(&data)
+		.map_err(|e| format!("Failed to parse {}: expected json array of test vectors: {}", filepath, e))
+}
+
+#[cfg(feature = "std")]
+fn error_matches(err: &ExitError, expected: &str) -> bool {
+	match err {
+		ExitError::Other(message) => message == expected,
+		err => format!("{:?}", err) == expected,
+	}
 }
 
 /// Tests a precompile against the ethereum consensus tests defined in the given file at filepath.
 /// The file is expected to be in JSON format and contain an array of test vectors, where each
 /// vector can be deserialized into an "EthConsensusTest".
+///
+/// Vectors carrying an `ExpectedError` must fail with that error; all others must succeed
+/// with the `Expected` output. The first mismatch is returned as an error.
 #[cfg(feature = "std")]
 pub fn test_precompile_test_vectors<P: Precompile>(filepath: &str)
 	-> std::result::Result<(), String>
 {
-	use std::fs;
-
-	let data = fs::read_to_string(&filepath)
-		.expect("Failed to read blake2F.json");
-
-	let tests: Vec<EthConsensusTest> = serde_json::from_str(&data)
-		.expect("expected json array");
-
-	for test in tests {
-		let input: Vec<u8> = hex::decode(test.Input)
-			.expect("Could not hex-decode test input data");
+	let context = Context {
+		address: Default::default(),
+		caller: Default::default(),
+		apparent_value: From::from(0),
+	};
 
-		let cost: u64 = 10000000;
+	test_precompile_test_vectors_with_context::<P>(filepath, &context)
+}
 
-		let context: Context = Context {
-			address: Default::default(),
-			caller: Default::default(),
-			apparent_value: From::from(0),
-		};
+/// Same as `test_precompile_test_vectors`, using `context` for every vector that does not
+/// override the caller, address or value itself.
+#[cfg(feature = "std")]
+pub fn test_precompile_test_vectors_with_context<P: Precompile>(filepath: &str, context: &Context)
+	-> std::result::Result<(), String>
+{
+	for test in read_test_vectors(filepath)? {
+		let input = test.input()?;
+		let context = test.context(context)?;
+		let gas_limit = test.GasLimit.unwrap_or(DEFAULT_GAS_LIMIT);
 
-		match P::execute(&input, Some(cost), &context) {
-			Ok(result) => {
+		match (P::execute(&input, Some(gas_limit), &context), &test.ExpectedError) {
+			(Ok(result), None) => {
+				if result.exit_status != ExitSucceed::Returned {
+					return Err(format!("Test '{}' returned {:?} (expected 'Returned')",
+						test.Name, result.exit_status));
+				}
 				let as_hex: String = hex::encode(result.output);
-				assert_eq!(result.exit_status, ExitSucceed::Returned,
-						"test '{}' returned {:?} (expected 'Returned')", test.Name, result.exit_status);
-				assert_eq!(as_hex, test.Expected,
-						"test '{}' failed (different output)", test.Name);
+				if as_hex != test.Expected {
+					return Err(format!("Test '{}' returned {} (expected {})",
+						test.Name, as_hex, test.Expected));
+				}
 				if let Some(expected_gas) = test.Gas {
-					assert_eq!(result.cost, expected_gas,
-							"test '{}' failed (different gas cost)", test.Name);
+					if result.cost != expected_gas {
+						return Err(format!("Test '{}' cost {} gas (expected {})",
+							test.Name, result.cost, expected_gas));
+					}
+				}
+			},
+			(Ok(result), Some(expected_err)) => {
+				return Err(format!("Test '{}' succeeded with output {} (expected error '{}')",
+					test.Name, hex::encode(result.output), expected_err));
+			},
+			(Err(err), Some(expected_err)) => {
+				if !error_matches(&err, expected_err) {
+					return Err(format!("Test '{}' failed with {:?} (expected error '{}')",
+						test.Name, err, expected_err));
 				}
 			},
-			Err(err) => {
+			(Err(err), None) => {
 				return Err(format!("Test '{}' returned error: {:?}", test.Name, err));
 			}
 		}
@@ -78,3 +168,83 @@ pub fn test_precompile_test_vectors<P: Precompile>(filepath: &str)
 
 	Ok(())
 }
+
+/// Timing of a single test vector, as measured by `benchmark_precompile_test_vectors`.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct BenchmarkResult {
+	pub name: String,
+	/// Gas reported by the precompile.
+	pub cost: u64,
+	/// Average wall-clock time of one execution.
+	pub elapsed: std::time::Duration,
+}
+
+#[cfg(feature = "std")]
+impl BenchmarkResult {
+	/// Throughput in gas per second, useful to compare the pricing of different precompiles.
+	pub fn gas_per_second(&self) -> f64 {
+		self.cost as f64 / self.elapsed.as_secs_f64()
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::fmt::Display for BenchmarkResult {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}: {:?} ({:.0} gas/s)", self.name, self.elapsed, self.gas_per_second())
+	}
+}
+
+/// Runs every successful vector of the given file `iterations` times and reports the average
+/// execution time. Failure vectors and vectors marked `NoBenchmark` are skipped.
+#[cfg(feature = "std")]
+pub fn benchmark_precompile_test_vectors<P: Precompile>(filepath: &str, iterations: u32)
+	-> std::result::Result<Vec<BenchmarkResult>, String>
+{
+	let base = Context {
+		address: Default::default(),
+		caller: Default::default(),
+		apparent_value: From::from(0),
+	};
+	let iterations = iterations.max(1);
+	let mut results = Vec::new();
+
+	for test in read_test_vectors(filepath)? {
+		if test.NoBenchmark || test.ExpectedError.is_some() {
+			continue;
+		}
+
+		let input = test.input()?;
+		let context = test.context(&base)?;
+		let gas_limit = test.GasLimit.unwrap_or(DEFAULT_GAS_LIMIT);
+
+		let mut cost = 0;
+		let start = std::time::Instant::now();
+		for _ in 0..iterations {
+			let output: PrecompileOutput = P::execute(&input, Some(gas_limit), &context)
+				.map_err(|err| format!("Test '{}' returned error: {:?}", test.Name, err))?;
+			cost = output.cost;
+		}
+
+		results.push(BenchmarkResult {
+			name: test.Name,
+			cost,
+			elapsed: start.elapsed() / iterations,
+		});
+	}
+
+	Ok(results)
+}
+
+/// Benchmarks the vectors of the given file as `benchmark_precompile_test_vectors` does, and
+/// reports one line per vector on stderr.
+#[cfg(feature = "std")]
+pub fn report_precompile_benchmark<P: Precompile>(filepath: &str, iterations: u32)
+	-> std::result::Result<(), String>
+{
+	for result in benchmark_precompile_test_vectors::<P>(filepath, iterations)? {
+		eprintln!("{}", result);
+	}
+
+	Ok(())
+}
@@ -22,6 +22,8 @@ use ethereum::{Log, Block as EthereumBlock};
 use ethereum_types::Bloom;
 use codec::{Encode, Decode};
 use sp_std::vec::Vec;
+use sp_runtime::traits::{Block as BlockT, BlakeTwo256};
+use fp_vm::tracing::{TracerKind, TransactionTrace};
 
 #[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
 pub struct TransactionStatus {
@@ -48,8 +50,41 @@ impl Default for TransactionStatus {
 	}
 }
 
+/// Receipt of an Ethereum transaction, as defined by EIP-658.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct EIP658Receipt {
+	/// 1 if the transaction succeeded, 0 otherwise.
+	pub status_code: u8,
+	/// Gas used by the block up to and including this transaction.
+	pub cumulative_gas_used: U256,
+	pub logs_bloom: Bloom,
+	pub logs: Vec<Log>,
+}
+
+impl rlp::Encodable for EIP658Receipt {
+	fn rlp_append(&self, s: &mut rlp::RlpStream) {
+		s.begin_list(4);
+		s.append(&self.status_code);
+		s.append(&self.cumulative_gas_used);
+		s.append(&self.logs_bloom);
+		s.append_list(&self.logs);
+	}
+}
+
+/// Substrate storage keys backing an Ethereum account, as proven by `eth_getProof`.
+#[derive(Eq, PartialEq, Clone, Encode, Decode, sp_runtime::RuntimeDebug)]
+pub struct AccountProofKeys {
+	/// `frame_system` account, holding the nonce and balance.
+	pub account: Vec<u8>,
+	/// `pallet_vm` contract code.
+	pub code: Vec<u8>,
+	/// `pallet_vm` storage slots, in the order they were requested.
+	pub storage: Vec<Vec<u8>>,
+}
+
 sp_api::decl_runtime_apis! {
 	/// API necessary for Ethereum-compatibility layer.
+	#[api_version(4)]
 	pub trait EthereumRuntimeRPCApi {
 		/// Returns runtime defined pallet_vm::ChainId.
 		fn chain_id() -> u64;
@@ -64,6 +99,7 @@ sp_api::decl_runtime_apis! {
 		/// For a given account address and index, returns pallet_vm::AccountStorages.
 		fn storage_at(address: H160, index: U256) -> H256;
 		/// Returns a frame_ethereum::call response. If `estimate` is true,
+		#[changed_in(2)]
 		fn call(
 			from: H160,
 			to: H160,
@@ -74,6 +110,19 @@ sp_api::decl_runtime_apis! {
 			nonce: Option<U256>,
 			estimate: bool,
 		) -> Result<fp_vm::CallInfo, sp_runtime::DispatchError>;
+		/// Returns a frame_ethereum::call response, executed after applying the
+		/// given state overrides. If `estimate` is true,
+		fn call(
+			from: H160,
+			to: H160,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+			nonce: Option<U256>,
+			estimate: bool,
+			state_overrides: Vec<(H160, fp_vm::StateOverride)>,
+		) -> Result<fp_vm::CallInfo, sp_runtime::DispatchError>;
 		/// Returns a frame_ethereum::create response.
 		fn create(
 			from: H160,
@@ -87,18 +136,161 @@ sp_api::decl_runtime_apis! {
 		/// Return the current block.
 		fn current_block() -> Option<EthereumBlock>;
 		/// Return the current receipt.
+		#[changed_in(4)]
 		fn current_receipts() -> Option<Vec<ethereum::Receipt>>;
+		/// Return the current receipts.
+		fn current_receipts() -> Option<Vec<EIP658Receipt>>;
 		/// Return the current transaction status.
 		fn current_transaction_statuses() -> Option<Vec<TransactionStatus>>;
 		/// Return all the current data for a block in a single runtime call.
+		#[changed_in(4)]
 		fn current_all() -> (
 			Option<EthereumBlock>,
 			Option<Vec<ethereum::Receipt>>,
 			Option<Vec<TransactionStatus>>
 		);
+		/// Return all the current data for a block in a single runtime call.
+		fn current_all() -> (
+			Option<EthereumBlock>,
+			Option<Vec<EIP658Receipt>>,
+			Option<Vec<TransactionStatus>>
+		);
+		/// Returns the storage keys to prove the account and storage slots of an address.
+		fn account_proof_keys(address: H160, storage_keys: Vec<H256>) -> AccountProofKeys;
+	}
+
+	/// API for re-executing transactions with a tracer attached.
+	#[api_version(2)]
+	pub trait DebugRuntimeApi {
+		/// Apply `extrinsics` in order up to the one carrying `transaction`, and
+		/// return the trace of that one. Meant to be called on top of
+		/// `Core::initialize_block` for the block being traced.
+		fn trace_transaction(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction: ethereum::Transaction,
+			tracer: TracerKind,
+		) -> Result<TransactionTrace, sp_runtime::DispatchError>;
+		/// Execute a call or a contract creation, when `to` is `None`, with a
+		/// tracer attached.
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+			nonce: Option<U256>,
+			tracer: TracerKind,
+		) -> Result<TransactionTrace, sp_runtime::DispatchError>;
+		/// Apply `extrinsics` and return the trace of every Ethereum transaction
+		/// that was executed, keyed by transaction hash, in block order. A trace
+		/// that could not be produced is reported in place. Meant to be called on
+		/// top of `Core::initialize_block` for the block being traced.
+		fn trace_block(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			tracer: TracerKind,
+		) -> Result<Vec<(H256, Result<TransactionTrace, sp_runtime::DispatchError>)>, sp_runtime::DispatchError>;
+	}
+
+	/// API for inspecting the Ethereum transactions of the transaction pool.
+	pub trait TxPoolRuntimeApi {
+		/// Return the Ethereum transactions carried by `extrinsics`, in order.
+		fn extrinsic_filter(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Vec<ethereum::Transaction>;
 	}
 }
 
+/// Check a proof returned by `eth_getProof` against the state root of the block it was
+/// generated at. Returns the raw value stored under `key`, or `None` if the proof shows
+/// that there is none.
+pub fn verify_proof(
+	state_root: H256,
+	proof: Vec<Vec<u8>>,
+	key: &[u8],
+) -> Result<Option<Vec<u8>>, &'static str> {
+	let db = sp_trie::StorageProof::new(proof).into_memory_db::<BlakeTwo256>();
+	sp_trie::read_trie_value::<sp_trie::Layout<BlakeTwo256>, _>(&db, &state_root, key)
+		.map_err(|_| "invalid proof")
+}
+
 pub trait ConvertTransaction<E> {
 	fn convert_transaction(&self, transaction: ethereum::Transaction) -> E;
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_trie::{Layout, MemoryDB, Recorder, Trie, TrieDB, TrieDBMut, TrieMut};
+
+	fn build_trie(entries: &[(&[u8], &[u8])]) -> (MemoryDB<BlakeTwo256>, H256) {
+		let mut db = MemoryDB::<BlakeTwo256>::default();
+		let mut root = H256::default();
+		{
+			let mut trie = TrieDBMut::<Layout<BlakeTwo256>>::new(&mut db, &mut root);
+			for (key, value) in entries {
+				trie.insert(key, value).unwrap();
+			}
+		}
+		(db, root)
+	}
+
+	fn generate_proof(db: &MemoryDB<BlakeTwo256>, root: &H256, key: &[u8]) -> Vec<Vec<u8>> {
+		let trie = TrieDB::<Layout<BlakeTwo256>>::new(db, root).unwrap();
+		let mut recorder = Recorder::new();
+		trie.get_with(key, &mut recorder).unwrap();
+		recorder.drain().into_iter().map(|record| record.data).collect()
+	}
+
+	const ENTRIES: &[(&[u8], &[u8])] = &[
+		(b"account", b"balance"),
+		(b"account_code", b"code"),
+		(b"storage", b"value"),
+	];
+
+	#[test]
+	fn verify_proof_round_trip() {
+		let (db, root) = build_trie(ENTRIES);
+
+		for (key, value) in ENTRIES {
+			let proof = generate_proof(&db, &root, key);
+			assert_eq!(verify_proof(root, proof, key), Ok(Some(value.to_vec())));
+		}
+	}
+
+	#[test]
+	fn verify_proof_of_absence() {
+		let (db, root) = build_trie(ENTRIES);
+
+		let proof = generate_proof(&db, &root, b"missing");
+		assert_eq!(verify_proof(root, proof, b"missing"), Ok(None));
+	}
+
+	#[test]
+	fn verify_proof_rejects_tampered_proof() {
+		let (db, root) = build_trie(ENTRIES);
+
+		let mut proof = generate_proof(&db, &root, b"storage");
+		let last = proof[0].len() - 1;
+		proof[0][last] ^= 0xff;
+		assert_eq!(verify_proof(root, proof, b"storage"), Err("invalid proof"));
+	}
+
+	#[test]
+	fn verify_proof_rejects_incomplete_proof() {
+		let (db, root) = build_trie(ENTRIES);
+
+		let mut proof = generate_proof(&db, &root, b"storage");
+		proof.pop();
+		assert_eq!(verify_proof(root, proof, b"storage"), Err("invalid proof"));
+	}
+
+	#[test]
+	fn verify_proof_rejects_other_state_root() {
+		let (db, root) = build_trie(ENTRIES);
+		let (_, other_root) = build_trie(&[(b"storage", b"other")]);
+
+		let proof = generate_proof(&db, &root, b"storage");
+		assert_eq!(verify_proof(other_root, proof, b"storage"), Err("invalid proof"));
+	}
+}
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Solidity ABI helpers shared by precompiles exposing a contract interface.
+
+use sp_std::vec::Vec;
+use sp_core::{H160, U256};
+use evm::ExitError;
+
+/// Get the `index`-th 32-byte word of the call arguments.
+pub fn argument(args: &[u8], index: usize) -> Result<&[u8], ExitError> {
+	let start = index * 32;
+	args.get(start..start + 32).ok_or_else(|| ExitError::Other("input too short".into()))
+}
+
+/// Decode the `index`-th argument as an `address`, rejecting dirty upper bytes.
+pub fn decode_address(args: &[u8], index: usize) -> Result<H160, ExitError> {
+	let word = argument(args, index)?;
+	if word[0..12].iter().any(|b| *b != 0) {
+		return Err(ExitError::Other("invalid address".into()))
+	}
+
+	Ok(H160::from_slice(&word[12..32]))
+}
+
+/// Decode the `index`-th argument as an `uint256`.
+pub fn decode_u256(args: &[u8], index: usize) -> Result<U256, ExitError> {
+	Ok(U256::from_big_endian(argument(args, index)?))
+}
+
+/// Encode an `uint256`.
+pub fn encode_u256(value: U256) -> Vec<u8> {
+	let mut out = [0u8; 32];
+	value.to_big_endian(&mut out);
+	out.to_vec()
+}
+
+/// Encode a `bool`.
+pub fn encode_bool(value: bool) -> Vec<u8> {
+	encode_u256(if value { U256::one() } else { U256::zero() })
+}
+
+/// Encode a `string` as the only return value.
+pub fn encode_string(value: &str) -> Vec<u8> {
+	let bytes = value.as_bytes();
+	let padded_len = (bytes.len() + 31) / 32 * 32;
+
+	let mut out = Vec::with_capacity(64 + padded_len);
+	out.extend_from_slice(&encode_u256(U256::from(32)));
+	out.extend_from_slice(&encode_u256(U256::from(bytes.len())));
+	out.extend_from_slice(bytes);
+	out.resize(64 + padded_len, 0);
+	out
+}
+
+/// Encode a `bytes32[]` as the only return value.
+pub fn encode_bytes32_array(words: &[[u8; 32]]) -> Vec<u8> {
+	let mut out = Vec::with_capacity(64 + 32 * words.len());
+	out.extend_from_slice(&encode_u256(U256::from(32)));
+	out.extend_from_slice(&encode_u256(U256::from(words.len())));
+	for word in words {
+		out.extend_from_slice(word);
+	}
+	out
+}
+
+/// Compute the selector of a function signature, such as `transfer(address,uint256)`.
+#[cfg(feature = "std")]
+pub fn selector(signature: &str) -> [u8; 4] {
+	let mut out = [0u8; 4];
+	out.copy_from_slice(&sp_core::hashing::keccak_256(signature.as_bytes())[0..4]);
+	out
+}
+
+/// Assert that each selector constant matches the selector of its signature.
+#[cfg(feature = "std")]
+#[macro_export]
+macro_rules! assert_selectors {
+	( $( $signature:expr => $selector:expr ),* $(,)? ) => {
+		$(
+			assert_eq!($crate::abi::selector($signature), $selector, "selector of {}", $signature);
+		)*
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decode_address_rejects_dirty_bytes() {
+		let mut word = [0u8; 32];
+		word[12..].copy_from_slice(&[0x11; 20]);
+		assert_eq!(decode_address(&word, 0).unwrap(), H160::repeat_byte(0x11));
+
+		word[0] = 1;
+		assert!(decode_address(&word, 0).is_err());
+		assert!(decode_address(&word[..31], 0).is_err());
+	}
+
+	#[test]
+	fn string_is_abi_encoded() {
+		let encoded = encode_string("Native");
+
+		assert_eq!(encoded.len(), 3 * 32);
+		assert_eq!(U256::from_big_endian(&encoded[0..32]), U256::from(32));
+		assert_eq!(U256::from_big_endian(&encoded[32..64]), U256::from(6));
+		assert_eq!(&encoded[64..70], b"Native");
+		assert!(encoded[70..].iter().all(|b| *b == 0));
+	}
+
+	#[test]
+	fn bytes32_array_is_abi_encoded() {
+		let encoded = encode_bytes32_array(&[[1u8; 32], [2u8; 32]]);
+
+		assert_eq!(encoded.len(), 4 * 32);
+		assert_eq!(U256::from_big_endian(&encoded[0..32]), U256::from(32));
+		assert_eq!(U256::from_big_endian(&encoded[32..64]), U256::from(2));
+		assert_eq!(&encoded[64..96], &[1u8; 32]);
+		assert_eq!(&encoded[96..128], &[2u8; 32]);
+	}
+
+	#[test]
+	fn selector_matches_known_value() {
+		assert_eq!(selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+	}
+}
@@ -18,18 +18,20 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod precompile;
+pub mod abi;
+pub mod tracing;
 
 use codec::{Encode, Decode};
 #[cfg(feature = "std")]
 use serde::{Serialize, Deserialize};
 use sp_std::vec::Vec;
 use sp_std::convert::From;
-use sp_core::{U256, H160};
+use sp_core::{U256, H160, H256};
 pub use evm::{ExitReason, ExitSucceed, ExitError, ExitFatal, ExitRevert};
 #[cfg(feature = "std")]
 pub use ssvm::types::StatusCode;
 pub use evm::backend::{Basic as Account, Log};
-pub use precompile::{Precompile, PrecompileSet, LinearCostPrecompile};
+pub use precompile::{Precompile, PrecompileSet, LinearCostPrecompile, ensure_gas};
 
 #[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
 #[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
@@ -180,3 +182,21 @@ pub enum CallOrCreateInfo {
 	Call(CallInfo),
 	Create(CreateInfo),
 }
+
+/// Temporary changes to an account applied before executing a call, as used by
+/// `eth_call` state overrides. `state` replaces the whole account storage while
+/// `state_diff` only replaces the given slots.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct StateOverride {
+	/// Balance to set for the account.
+	pub balance: Option<U256>,
+	/// Nonce to set for the account.
+	pub nonce: Option<U256>,
+	/// Code to set for the account, either EVM bytecode or wasm.
+	pub code: Option<Vec<u8>>,
+	/// Storage that replaces the whole account storage.
+	pub state: Option<Vec<(H256, H256)>>,
+	/// Storage slots to set, leaving the others untouched.
+	pub state_diff: Option<Vec<(H256, H256)>>,
+}
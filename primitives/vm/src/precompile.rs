@@ -118,3 +118,11 @@ fn ensure_linear_cost(
 
 	Ok(cost)
 }
+
+/// Ensure that `cost` fits in the gas available to a precompile.
+pub fn ensure_gas(target_gas: Option<u64>, cost: u64) -> Result<(), ExitError> {
+	match target_gas {
+		Some(gas) if cost > gas => Err(ExitError::OutOfGas),
+		_ => Ok(()),
+	}
+}
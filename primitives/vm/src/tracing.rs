@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: Apache-2.0
+// This file is part of Frontier.
+//
+// Copyright (c) 2020 Parity Technologies (UK) Ltd.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Execution traces returned by the runtime to the `debug` RPC namespace.
+
+use codec::{Encode, Decode};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
+use sp_std::vec::Vec;
+use sp_core::{U256, H160, H256};
+
+/// Tracer to attach to an execution.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum TracerKind {
+	/// Opcode level logs, as produced by the default geth tracer.
+	StructLog(StructLogConfig),
+	/// Tree of call frames, as produced by the geth `callTracer`.
+	Call,
+}
+
+/// What to leave out of the struct logs.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, Default)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct StructLogConfig {
+	pub disable_storage: bool,
+	pub disable_memory: bool,
+	pub disable_stack: bool,
+}
+
+/// State of the EVM before executing a single opcode.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct StructLog {
+	/// Program counter.
+	pub pc: u64,
+	/// Opcode about to be executed.
+	pub op: u8,
+	/// Gas left before executing the opcode.
+	pub gas: u64,
+	/// Gas charged for the opcode.
+	pub gas_cost: u64,
+	/// Call depth, starting at 1.
+	pub depth: u32,
+	/// Stack, bottom first.
+	pub stack: Option<Vec<H256>>,
+	/// Memory contents.
+	pub memory: Option<Vec<u8>>,
+	/// Storage slots of the current contract accessed so far.
+	pub storage: Option<Vec<(H256, H256)>>,
+}
+
+/// How a call frame was entered.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum CallType {
+	Call,
+	CallCode,
+	DelegateCall,
+	StaticCall,
+	Create,
+	Create2,
+	SelfDestruct,
+}
+
+/// Virtual machine that executed a call frame.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum FrameVm {
+	Evm,
+	/// Wasm contracts executed by SSVM.
+	Ewasm,
+}
+
+/// A call frame and the frames it entered.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub struct CallFrame {
+	pub call_type: CallType,
+	pub vm: FrameVm,
+	pub from: H160,
+	pub to: H160,
+	pub value: U256,
+	pub gas: u64,
+	pub gas_used: u64,
+	pub input: Vec<u8>,
+	pub output: Vec<u8>,
+	/// Error message if the frame did not succeed.
+	pub error: Option<Vec<u8>>,
+	pub calls: Vec<CallFrame>,
+}
+
+/// Trace of a whole transaction.
+#[derive(Clone, Eq, PartialEq, Encode, Decode)]
+#[cfg_attr(feature = "std", derive(Debug, Serialize, Deserialize))]
+pub enum TransactionTrace {
+	StructLogs {
+		/// Gas used by the transaction.
+		gas: u64,
+		failed: bool,
+		return_value: Vec<u8>,
+		struct_logs: Vec<StructLog>,
+	},
+	CallTrace(CallFrame),
+}
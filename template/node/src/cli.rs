@@ -40,6 +40,10 @@ pub struct RunCmd {
 	#[structopt(long, default_value = "10000")]
 	pub max_past_logs: u32,
 
+	/// Keep the Frontier database in memory, it is lost when the node stops. For development only.
+	#[structopt(long = "frontier-in-memory-db")]
+	pub frontier_in_memory_db: bool,
+
 	/// The dynamic-fee pallet target gas price set by block author
 	#[structopt(long, default_value = "1")]
 	pub target_gas_price: u64,
@@ -12,15 +12,18 @@ use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sc_rpc_api::DenyUnsafe;
 use sc_client_api::{
 	backend::{StorageProvider, Backend, StateBackend, AuxStore},
-	client::BlockchainEvents
+	client::BlockchainEvents, BlockBackend, ProofProvider,
 };
 use sc_rpc::SubscriptionTaskExecutor;
+use sc_transaction_graph::{ChainApi, Pool};
 use sp_runtime::traits::BlakeTwo256;
 use sp_block_builder::BlockBuilder;
 use sc_network::NetworkService;
+use sp_utils::mpsc::TracingUnboundedSender;
 use jsonrpc_pubsub::manager::SubscriptionManager;
 use pallet_ethereum::EthereumStorageSchema;
-use fc_rpc::{StorageOverride, SchemaV1Override, OverrideHandle, RuntimeApiStorageOverride};
+use fc_mapping_sync::SharedMappingLag;
+use fc_rpc::{StorageOverride, SchemaV1Override, SchemaV2Override, OverrideHandle, RuntimeApiStorageOverride};
 
 /// Light client extra dependencies.
 pub struct LightDeps<C, F, P> {
@@ -35,11 +38,13 @@ pub struct LightDeps<C, F, P> {
 }
 
 /// Full client dependencies.
-pub struct FullDeps<C, P> {
+pub struct FullDeps<C, P, A: ChainApi> {
 	/// The client instance to use.
 	pub client: Arc<C>,
 	/// Transaction pool instance.
 	pub pool: Arc<P>,
+	/// Graph of the transaction pool.
+	pub graph: Arc<Pool<A>>,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
 	/// The Node authority flag
@@ -48,12 +53,16 @@ pub struct FullDeps<C, P> {
 	pub enable_dev_signer: bool,
 	/// Network service
 	pub network: Arc<NetworkService<Block, Hash>>,
+	/// Channel to the system RPC handler, used to query the sync state.
+	pub system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<Block>>,
 	/// Ethereum pending transactions.
 	pub pending_transactions: PendingTransactions,
 	/// EthFilterApi pool.
 	pub filter_pool: Option<FilterPool>,
 	/// Backend.
 	pub backend: Arc<fc_db::Backend<Block>>,
+	/// Mapping lag measured by the mapping sync worker.
+	pub mapping_lag: SharedMappingLag<Block>,
 	/// Maximum number of logs in a query.
 	pub max_past_logs: u32,
 	/// Manual seal command sink
@@ -61,41 +70,48 @@ pub struct FullDeps<C, P> {
 }
 
 /// Instantiate all Full RPC extensions.
-pub fn create_full<C, P, BE>(
-	deps: FullDeps<C, P>,
+pub fn create_full<C, P, BE, A>(
+	deps: FullDeps<C, P, A>,
 	subscription_task_executor: SubscriptionTaskExecutor
 ) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
 	BE: Backend<Block> + 'static,
 	BE::State: StateBackend<BlakeTwo256>,
 	C: ProvideRuntimeApi<Block> + StorageProvider<Block, BE> + AuxStore,
-	C: BlockchainEvents<Block>,
+	C: BlockchainEvents<Block> + BlockBackend<Block> + ProofProvider<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError>,
 	C: Send + Sync + 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: BlockBuilder<Block>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: fp_rpc::EthereumRuntimeRPCApi<Block>,
+	C::Api: fp_rpc::DebugRuntimeApi<Block>,
+	C::Api: fp_rpc::TxPoolRuntimeApi<Block>,
 	P: TransactionPool<Block=Block> + 'static,
+	A: ChainApi<Block=Block> + 'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use fc_rpc::{
 		EthApi, EthApiServer, EthFilterApi, EthFilterApiServer, NetApi, NetApiServer,
 		EthPubSubApi, EthPubSubApiServer, Web3Api, Web3ApiServer, EthDevSigner, EthSigner,
-		HexEncodedIdProvider,
+		HexEncodedIdProvider, DebugApi, DebugApiServer, TraceApi, TraceApiServer,
+		TxPoolApi, TxPoolApiServer, FrontierApi, FrontierApiServer,
 	};
 
 	let mut io = jsonrpc_core::IoHandler::default();
 	let FullDeps {
 		client,
 		pool,
+		graph,
 		deny_unsafe,
 		is_authority,
 		network,
+		system_rpc_tx,
 		pending_transactions,
 		filter_pool,
 		command_sink,
 		backend,
+		mapping_lag,
 		max_past_logs,
 		enable_dev_signer,
 	} = deps;
@@ -116,6 +132,10 @@ pub fn create_full<C, P, BE>(
 		EthereumStorageSchema::V1,
 		Box::new(SchemaV1Override::new(client.clone())) as Box<dyn StorageOverride<_> + Send + Sync>
 	);
+	overrides_map.insert(
+		EthereumStorageSchema::V2,
+		Box::new(SchemaV2Override::new(client.clone())) as Box<dyn StorageOverride<_> + Send + Sync>
+	);
 
 	let overrides = Arc::new(OverrideHandle {
 		schemas: overrides_map,
@@ -128,19 +148,54 @@ pub fn create_full<C, P, BE>(
 			pool.clone(),
 			frontier_template_runtime::TransactionConverter,
 			network.clone(),
+			system_rpc_tx.clone(),
 			pending_transactions.clone(),
 			signers,
 			overrides.clone(),
-			backend,
+			backend.clone(),
+			mapping_lag.clone(),
 			is_authority,
 			max_past_logs,
 		))
 	);
 
+	io.extend_with(
+		DebugApiServer::to_delegate(DebugApi::new(
+			client.clone(),
+			backend.clone(),
+			overrides.clone(),
+		))
+	);
+
+	io.extend_with(
+		TraceApiServer::to_delegate(TraceApi::new(
+			client.clone(),
+			backend.clone(),
+			overrides.clone(),
+			max_past_logs,
+		))
+	);
+
+	io.extend_with(
+		TxPoolApiServer::to_delegate(TxPoolApi::new(
+			client.clone(),
+			graph,
+		))
+	);
+
+	io.extend_with(
+		FrontierApiServer::to_delegate(FrontierApi::new(
+			client.clone(),
+			backend.clone(),
+			mapping_lag,
+		))
+	);
+
 	if let Some(filter_pool) = filter_pool {
 		io.extend_with(
 			EthFilterApiServer::to_delegate(EthFilterApi::new(
 				client.clone(),
+				backend,
 				filter_pool.clone(),
 				500 as usize, // max stored filters
 				overrides.clone(),
@@ -169,6 +224,7 @@ pub fn create_full<C, P, BE>(
 			pool.clone(),
 			client.clone(),
 			network.clone(),
+			system_rpc_tx,
 			SubscriptionManager::<HexEncodedIdProvider>::with_id_provider(
 				HexEncodedIdProvider::default(),
 				Arc::new(subscription_task_executor)
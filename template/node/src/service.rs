@@ -7,9 +7,9 @@ use sc_client_api::{ExecutorProvider, RemoteBackend, BlockchainEvents};
 #[cfg(feature = "manual-seal")]
 use sc_consensus_manual_seal::{self as manual_seal};
 use fc_consensus::FrontierBlockImport;
-use fc_mapping_sync::MappingSyncWorker;
+use fc_mapping_sync::{MappingSyncWorker, SharedMappingLag};
 use frontier_template_runtime::{self, opaque::Block, RuntimeApi, SLOT_DURATION};
-use sc_service::{error::Error as ServiceError, Configuration, TaskManager, BasePath};
+use sc_service::{error::Error as ServiceError, Configuration, TaskManager, BasePath, DatabaseConfig};
 use sp_inherents::{InherentDataProviders, ProvideInherentData, InherentIdentifier, InherentData};
 use sc_executor::native_executor_instance;
 pub use sc_executor::NativeExecutor;
@@ -83,20 +83,30 @@ impl ProvideInherentData for MockTimestampInherentDataProvider {
 	}
 }
 
-pub fn open_frontier_backend(config: &Configuration) -> Result<Arc<fc_db::Backend<Block>>, String> {
+pub fn open_frontier_backend(config: &Configuration, in_memory: bool) -> Result<Arc<fc_db::Backend<Block>>, String> {
 	let config_dir = config.base_path.as_ref()
 		.map(|base_path| base_path.config_dir(config.chain_spec.id()))
 		.unwrap_or_else(|| {
 			BasePath::from_project("", "", &crate::cli::Cli::executable_name())
 				.config_dir(config.chain_spec.id())
 		});
-	let database_dir = config_dir.join("frontier").join("db");
+	let source = match config.database {
+		// Development nodes may keep the Frontier database in memory, whatever the Substrate one.
+		_ if in_memory => fc_db::DatabaseSettingsSrc::InMemory,
+		DatabaseConfig::RocksDb { .. } => fc_db::DatabaseSettingsSrc::RocksDb {
+			path: config_dir.join("frontier").join("db"),
+			cache_size: 0,
+		},
+		DatabaseConfig::ParityDb { .. } => fc_db::DatabaseSettingsSrc::ParityDb {
+			path: config_dir.join("frontier").join("paritydb"),
+		},
+		_ => return Err(
+			"Supported db sources for Frontier: `rocksdb` | `paritydb`, or `--frontier-in-memory-db`".to_string()
+		),
+	};
 
 	Ok(Arc::new(fc_db::Backend::<Block>::new(&fc_db::DatabaseSettings {
-		source: fc_db::DatabaseSettingsSrc::RocksDb {
-			path: database_dir,
-			cache_size: 0,
-		}
+		source,
 	})?))
 }
 
@@ -147,7 +157,7 @@ pub fn new_partial(config: &Configuration, #[allow(unused_variables)] cli: &Cli)
 	let filter_pool: Option<FilterPool>
 		= Some(Arc::new(Mutex::new(BTreeMap::new())));
 
-	let frontier_backend = open_frontier_backend(config)?;
+	let frontier_backend = open_frontier_backend(config, cli.run.frontier_in_memory_db)?;
 
 	#[cfg(feature = "manual-seal")] {
 		let sealing = cli.run.sealing;
@@ -280,26 +290,33 @@ pub fn new_full(
 	let is_authority = role.is_authority();
 	let subscription_task_executor = sc_rpc::SubscriptionTaskExecutor::new(task_manager.spawn_handle());
 
+	let mapping_lag: SharedMappingLag<Block> = Default::default();
+
 	let rpc_extensions_builder = {
 		let client = client.clone();
 		let pool = transaction_pool.clone();
 		let network = network.clone();
+		let system_rpc_tx = system_rpc_tx.clone();
 		let pending = pending_transactions.clone();
 		let filter_pool = filter_pool.clone();
 		let frontier_backend = frontier_backend.clone();
+		let mapping_lag = mapping_lag.clone();
 		let max_past_logs = cli.run.max_past_logs;
 
 		Box::new(move |deny_unsafe, _| {
 			let deps = crate::rpc::FullDeps {
 				client: client.clone(),
 				pool: pool.clone(),
+				graph: pool.pool().clone(),
 				deny_unsafe,
 				is_authority,
 				enable_dev_signer,
 				network: network.clone(),
+				system_rpc_tx: system_rpc_tx.clone(),
 				pending_transactions: pending.clone(),
 				filter_pool: filter_pool.clone(),
 				backend: frontier_backend.clone(),
+				mapping_lag: mapping_lag.clone(),
 				max_past_logs,
 				command_sink: Some(command_sink.clone()),
 			};
@@ -318,6 +335,8 @@ pub fn new_full(
 			client.clone(),
 			backend.clone(),
 			frontier_backend.clone(),
+			mapping_lag,
+			prometheus_registry.as_ref(),
 		).for_each(|()| futures::future::ready(()))
 	);
 
@@ -341,10 +360,18 @@ pub fn new_full(
 			"frontier-filter-pool",
 			EthTask::filter_pool_task(
 					Arc::clone(&client),
-					filter_pool,
+					filter_pool.clone(),
 					FILTER_RETAIN_THRESHOLD,
 			)
 		);
+		task_manager.spawn_essential_handle().spawn(
+			"frontier-pending-transaction-filter",
+			EthTask::pending_transaction_filter_task(
+					Arc::clone(&client),
+					transaction_pool.clone(),
+					filter_pool,
+			)
+		);
 	}
 
 	// Spawn Frontier pending transactions maintenance task (as essential, otherwise we leak).
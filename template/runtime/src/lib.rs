@@ -44,7 +44,7 @@ pub use frame_support::{
 };
 use pallet_vm::{
 	Account as ETHAccount, FeeCalculator, HashedAddressMapping,
-	EnsureAddressTruncated, Runner,
+	EnsureAddressTruncated, Runner, AddressMapping,
 };
 use fp_rpc::TransactionStatus;
 use pallet_transaction_payment::CurrencyAdapter;
@@ -301,9 +301,27 @@ impl pallet_sudo::Config for Runtime {
 }
 
 
+pub struct NativeErc20Metadata;
+
+impl pallet_vm_precompile_balances_erc20::Erc20Metadata for NativeErc20Metadata {
+	fn name() -> &'static str {
+		"Unit"
+	}
+
+	fn symbol() -> &'static str {
+		"UNIT"
+	}
+
+	fn decimals() -> u8 {
+		18
+	}
+}
+
 parameter_types! {
 	pub const ChainId: u64 = 123;
 	pub BlockGasLimit: U256 = U256::from(u32::max_value());
+	/// Address of the native currency ERC-20 precompile, the ninth of `Precompiles`.
+	pub NativeErc20Address: H160 = H160::from_low_u64_be(9);
 }
 
 impl pallet_vm::Config for Runtime {
@@ -320,10 +338,11 @@ impl pallet_vm::Config for Runtime {
 		pallet_vm_precompile_simple::Sha256,
 		pallet_vm_precompile_simple::Ripemd160,
 		pallet_vm_precompile_simple::Identity,
-		pallet_vm_precompile_modexp::Modexp,
+		pallet_vm_precompile_modexp::Modexp<pallet_vm_precompile_modexp::Eip2565>,
 		pallet_vm_precompile_simple::ECRecoverPublicKey,
 		pallet_vm_precompile_sha3fips::Sha3FIPS256,
 		pallet_vm_precompile_sha3fips::Sha3FIPS512,
+		pallet_vm_precompile_balances_erc20::Erc20Balances<Self, NativeErc20Metadata, NativeErc20Address>,
 	);
 	type ChainId = ChainId;
 	type BlockGasLimit = BlockGasLimit;
@@ -431,6 +450,38 @@ pub type Executive = frame_executive::Executive<
 	AllModules
 >;
 
+/// Fail if the runtime was built without the `tracing` feature, traces would miss every EVM frame.
+fn ensure_tracing_enabled() -> Result<(), sp_runtime::DispatchError> {
+	if !pallet_vm::tracing::ENABLED {
+		return Err(sp_runtime::DispatchError::Other("runtime was built without tracing"));
+	}
+	Ok(())
+}
+
+/// Apply an extrinsic carrying an Ethereum transaction with a tracer attached, and return
+/// the transaction hash along with its trace. Returns `None` if the transaction was not
+/// executed, it is then not part of the Ethereum block.
+fn trace_ethereum_extrinsic(
+	ext: UncheckedExtrinsic,
+	transaction: &pallet_ethereum::Transaction,
+	tracer: pallet_vm::tracing::TracerKind,
+) -> Option<(H256, Result<pallet_vm::tracing::TransactionTrace, sp_runtime::DispatchError>)> {
+	let executed_before = Ethereum::pending().len();
+	let (_, tracer) = pallet_vm::tracing::trace(tracer, || Executive::apply_extrinsic(ext));
+	let pending = Ethereum::pending();
+	let (_, status, _) = pending.get(executed_before)?;
+	let cumulative_gas_used = |index: usize| pending.get(index)
+		.map(|(_, _, receipt)| receipt.cumulative_gas_used)
+		.unwrap_or_default();
+	let used_gas = cumulative_gas_used(executed_before)
+		.saturating_sub(executed_before.checked_sub(1).map(cumulative_gas_used).unwrap_or_default())
+		.low_u64();
+
+	let trace = tracer.finish(transaction.gas_limit.low_u64(), used_gas)
+		.ok_or(sp_runtime::DispatchError::Other("transaction was not traced"));
+	Some((status.transaction_hash, trace))
+}
+
 impl_runtime_apis! {
 	impl sp_api::Core<Block> for Runtime {
 		fn version() -> RuntimeVersion {
@@ -544,7 +595,12 @@ impl_runtime_apis! {
 			gas_price: Option<U256>,
 			nonce: Option<U256>,
 			estimate: bool,
+			state_overrides: Vec<(H160, pallet_vm::StateOverride)>,
 		) -> Result<pallet_vm::CallInfo, sp_runtime::DispatchError> {
+			for (address, state_override) in &state_overrides {
+				EVM::apply_state_override(address, state_override)?;
+			}
+
 			let config = if estimate {
 				let mut config = <Runtime as pallet_vm::Config>::config().clone();
 				config.estimate = true;
@@ -601,13 +657,13 @@ impl_runtime_apis! {
 			Ethereum::current_block()
 		}
 
-		fn current_receipts() -> Option<Vec<pallet_ethereum::Receipt>> {
+		fn current_receipts() -> Option<Vec<pallet_ethereum::EIP658Receipt>> {
 			Ethereum::current_receipts()
 		}
 
 		fn current_all() -> (
 			Option<pallet_ethereum::Block>,
-			Option<Vec<pallet_ethereum::Receipt>>,
+			Option<Vec<pallet_ethereum::EIP658Receipt>>,
 			Option<Vec<TransactionStatus>>
 		) {
 			(
@@ -616,6 +672,121 @@ impl_runtime_apis! {
 				Ethereum::current_transaction_statuses()
 			)
 		}
+
+		fn account_proof_keys(address: H160, storage_keys: Vec<H256>) -> fp_rpc::AccountProofKeys {
+			let account_id = <Runtime as pallet_vm::Config>::AddressMapping::into_account_id(address);
+			fp_rpc::AccountProofKeys {
+				account: frame_system::Account::<Runtime>::hashed_key_for(&account_id),
+				code: pallet_vm::AccountCodes::<Runtime>::hashed_key_for(&address),
+				storage: storage_keys.iter()
+					.map(|key| pallet_vm::AccountStorages::<Runtime>::hashed_key_for(&address, key))
+					.collect(),
+			}
+		}
+	}
+
+	impl fp_rpc::DebugRuntimeApi<Block> for Runtime {
+		fn trace_transaction(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			transaction: pallet_ethereum::Transaction,
+			tracer: pallet_vm::tracing::TracerKind,
+		) -> Result<pallet_vm::tracing::TransactionTrace, sp_runtime::DispatchError> {
+			ensure_tracing_enabled()?;
+			for ext in extrinsics {
+				let is_traced = matches!(
+					&ext.function,
+					Call::Ethereum(pallet_ethereum::Call::transact(t)) if *t == transaction
+				);
+				if !is_traced {
+					let _ = Executive::apply_extrinsic(ext);
+					continue;
+				}
+
+				return trace_ethereum_extrinsic(ext, &transaction, tracer)
+					.map(|(_, trace)| trace)
+					.unwrap_or(Err(sp_runtime::DispatchError::Other("transaction was not executed")));
+			}
+
+			Err(sp_runtime::DispatchError::Other("transaction not found in the block"))
+		}
+
+		fn trace_block(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+			tracer: pallet_vm::tracing::TracerKind,
+		) -> Result<
+			Vec<(H256, Result<pallet_vm::tracing::TransactionTrace, sp_runtime::DispatchError>)>,
+			sp_runtime::DispatchError,
+		> {
+			ensure_tracing_enabled()?;
+			let mut traces = Vec::new();
+			for ext in extrinsics {
+				let transaction = match &ext.function {
+					Call::Ethereum(pallet_ethereum::Call::transact(t)) => t.clone(),
+					_ => {
+						let _ = Executive::apply_extrinsic(ext);
+						continue;
+					},
+				};
+
+				// Transactions that fail before execution are not part of the Ethereum block.
+				if let Some(trace) = trace_ethereum_extrinsic(ext, &transaction, tracer) {
+					traces.push(trace);
+				}
+			}
+
+			Ok(traces)
+		}
+
+		fn trace_call(
+			from: H160,
+			to: Option<H160>,
+			data: Vec<u8>,
+			value: U256,
+			gas_limit: U256,
+			gas_price: Option<U256>,
+			nonce: Option<U256>,
+			tracer: pallet_vm::tracing::TracerKind,
+		) -> Result<pallet_vm::tracing::TransactionTrace, sp_runtime::DispatchError> {
+			ensure_tracing_enabled()?;
+			let config = <Runtime as pallet_vm::Config>::config();
+			let (used_gas, tracer) = pallet_vm::tracing::trace(tracer, || -> Result<U256, sp_runtime::DispatchError> {
+				Ok(match to {
+					Some(to) => <Runtime as pallet_vm::Config>::Runner::call(
+						from,
+						to,
+						data,
+						value,
+						gas_limit.low_u64(),
+						gas_price,
+						nonce,
+						config,
+					).map_err(|err| err.into())?.used_gas,
+					None => <Runtime as pallet_vm::Config>::Runner::create(
+						from,
+						data,
+						value,
+						gas_limit.low_u64(),
+						gas_price,
+						nonce,
+						config,
+					).map_err(|err| err.into())?.used_gas,
+				})
+			});
+
+			tracer.finish(gas_limit.low_u64(), used_gas?.low_u64())
+				.ok_or(sp_runtime::DispatchError::Other("call was not executed"))
+		}
+	}
+
+	impl fp_rpc::TxPoolRuntimeApi<Block> for Runtime {
+		fn extrinsic_filter(
+			extrinsics: Vec<<Block as BlockT>::Extrinsic>,
+		) -> Vec<pallet_ethereum::Transaction> {
+			extrinsics.into_iter().filter_map(|xt| match xt.function {
+				Call::Ethereum(pallet_ethereum::Call::transact(t)) => Some(t),
+				_ => None,
+			}).collect()
+		}
 	}
 
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<